@@ -0,0 +1,713 @@
+//! 关键帧动画: 在时间轴上 按关键帧 插值 样式属性
+//!
+//! ```text
+//! Animation::new(Duration::from_millis(800))
+//!     .keyframe(0.5, "background-color: #ff4d4f; padding: 12")
+//!     .infinite()
+//!     .direction(Direction::Alternate)
+//! ```
+//! 没有 0 或 1 的关键帧时, 使用 属性原来的值; 动画结束 或 取消后 (没有 fill), 属性恢复为 原来的值.
+//! 时间 来自 App 的时钟, 测试时 可以注入 ManualClock
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::stylesheet::{parse_declarations, Property};
+use crate::theme::Theme;
+use crate::transition::{AnimatedValue, Easing};
+use crate::{DomElement, Style};
+
+/// 动画和过渡 使用的时钟
+pub trait Clock {
+    /// 从某个固定起点 开始经过的时间
+    fn now(&self) -> Duration;
+}
+
+/// 系统时钟, 起点为 创建时
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// 手动推进的时钟, 克隆后 共享同一个时间
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Rc<Cell<Duration>>);
+
+impl ManualClock {
+    pub fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+
+    pub fn set(&self, now: Duration) {
+        self.0.set(now);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+/// 播放方向
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Normal,
+    Reverse,
+    /// 奇数次迭代 正向, 偶数次迭代 反向
+    Alternate,
+    AlternateReverse,
+}
+
+impl Default for Direction {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// 开始前 (延迟期间) 和 结束后 是否保留 动画的值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillMode {
+    None,
+    /// 结束后 保留 最后的值
+    Forwards,
+    /// 延迟期间 使用 第一帧的值
+    Backwards,
+    Both,
+}
+
+impl Default for FillMode {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// 一个属性的 关键帧, 按 偏移量 排列
+#[derive(Debug, Clone, PartialEq)]
+struct Track {
+    property: Property,
+    keyframes: Vec<(f32, AnimatedValue)>,
+}
+
+impl Track {
+    /// 迭代进度 progress 处的值, 缺少的 0 和 1 关键帧 使用 underlying
+    fn sample(
+        &self,
+        progress: f32,
+        easing: Easing,
+        underlying: Option<AnimatedValue>,
+    ) -> Option<AnimatedValue> {
+        let mut frames = Vec::with_capacity(self.keyframes.len() + 2);
+        if let Some(underlying) = underlying {
+            if self.keyframes.first()?.0 > 0.0 {
+                frames.push((0.0, underlying));
+            }
+        }
+        frames.extend_from_slice(&self.keyframes);
+        if let Some(underlying) = underlying {
+            if self.keyframes.last()?.0 < 1.0 {
+                frames.push((1.0, underlying));
+            }
+        }
+
+        let i = frames
+            .iter()
+            .rposition(|(offset, _)| *offset <= progress)
+            .unwrap_or(0);
+        let (start, from) = frames[i];
+        let (end, to) = match frames.get(i + 1) {
+            Some(frame) => *frame,
+            None => return Some(from),
+        };
+        // 缓动曲线 作用于 每两个关键帧之间
+        let t = easing.sample((progress - start) / (end - start));
+        // 不能插值的值 在中点 切换
+        from.lerp(&to, t).or(Some(if t < 0.5 { from } else { to }))
+    }
+}
+
+/// 关键帧动画 的定义
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    tracks: Vec<Track>,
+    duration: Duration,
+    delay: Duration,
+    easing: Easing,
+    /// 迭代次数, 可以是小数, 无限循环 为 f32::INFINITY
+    iterations: f32,
+    direction: Direction,
+    fill: FillMode,
+}
+
+impl Animation {
+    /// duration 是 一次迭代的时长
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            tracks: Vec::new(),
+            duration,
+            delay: Duration::ZERO,
+            easing: Easing::Linear,
+            iterations: 1.0,
+            direction: Direction::default(),
+            fill: FillMode::default(),
+        }
+    }
+
+    /// offset (0 到 1) 处的关键帧, 如: .keyframe(0.5, "width: 100; background-color: #ff0000");
+    /// 值 在这里解析, 可以引用 默认主题 (浅色) 的令牌, 不随 App 切换主题; 不能插值的属性 忽略
+    pub fn keyframe(mut self, offset: f32, declarations: &str) -> Self {
+        let offset = offset.clamp(0.0, 1.0);
+        let theme = Theme::light();
+        for declaration in parse_declarations(declarations) {
+            let mut dom = DomElement::new("keyframe");
+            declaration.apply(&mut dom, &theme);
+            let value = match AnimatedValue::get(dom.style(), declaration.property) {
+                Some(value) => value,
+                None => continue,
+            };
+            let track = match self
+                .tracks
+                .iter_mut()
+                .position(|track| track.property == declaration.property)
+            {
+                Some(i) => &mut self.tracks[i],
+                None => {
+                    self.tracks.push(Track {
+                        property: declaration.property,
+                        keyframes: Vec::new(),
+                    });
+                    self.tracks.last_mut().unwrap()
+                }
+            };
+            // 偏移量相同的 关键帧 后添加的 覆盖先添加的
+            let i = track
+                .keyframes
+                .iter()
+                .position(|(o, _)| *o > offset)
+                .unwrap_or(track.keyframes.len());
+            if i > 0 && track.keyframes[i - 1].0 == offset {
+                track.keyframes[i - 1].1 = value;
+            } else {
+                track.keyframes.insert(i, (offset, value));
+            }
+        }
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// 每两个关键帧之间的 缓动曲线, 默认为 Linear
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn iterations(mut self, iterations: f32) -> Self {
+        self.iterations = iterations.max(0.0);
+        self
+    }
+
+    pub fn infinite(self) -> Self {
+        self.iterations(f32::INFINITY)
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn fill(mut self, fill: FillMode) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// 包括延迟的 总时长, 无限循环 或 超出 Duration 的范围时 返回 None, 和 无限循环 一样 不会结束
+    pub fn total_duration(&self) -> Option<Duration> {
+        if self.iterations.is_infinite() {
+            return None;
+        }
+        let active = self.duration.as_secs_f32() * self.iterations;
+        self.delay
+            .checked_add(Duration::try_from_secs_f32(active).ok()?)
+    }
+
+    pub fn is_finished(&self, time: Duration) -> bool {
+        matches!(self.total_duration(), Some(total) if time >= total)
+    }
+
+    /// time 时 迭代内的进度 (已考虑 播放方向), 没有效果时 返回 None
+    fn progress(&self, time: Duration) -> Option<f32> {
+        if time < self.delay {
+            return matches!(self.fill, FillMode::Backwards | FillMode::Both)
+                .then(|| self.directed(0, 0.0));
+        }
+        if self.is_finished(time) || self.duration.is_zero() {
+            if !matches!(self.fill, FillMode::Forwards | FillMode::Both) {
+                return None;
+            }
+            // 停在 最后一次迭代的 结束位置
+            let last = (self.iterations.ceil() - 1.0).max(0.0);
+            let progress = (self.iterations - last).min(1.0);
+            return Some(self.directed(last as u64, progress));
+        }
+        let iterations = (time - self.delay).as_secs_f32() / self.duration.as_secs_f32();
+        let iteration = iterations.floor();
+        Some(self.directed(iteration as u64, iterations - iteration))
+    }
+
+    fn directed(&self, iteration: u64, progress: f32) -> f32 {
+        let odd = iteration % 2 == 1;
+        let reverse = match self.direction {
+            Direction::Normal => false,
+            Direction::Reverse => true,
+            Direction::Alternate => odd,
+            Direction::AlternateReverse => !odd,
+        };
+        if reverse {
+            1.0 - progress
+        } else {
+            progress
+        }
+    }
+}
+
+/// 播放状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayState {
+    Running,
+    Paused,
+    Finished,
+    /// 已取消, 没有效果
+    Idle,
+}
+
+/// 请求 绘制新的一帧 的标记, App 和 它渲染过的 动画句柄 共享;
+/// 句柄的操作 设置它, 事件循环 检查并清除它, 暂停的动画 不需要 每帧检查
+#[derive(Debug, Clone, Default)]
+pub struct FrameRequest(Rc<Cell<bool>>);
+
+impl FrameRequest {
+    #[inline]
+    pub fn request(&self) {
+        self.0.set(true);
+    }
+
+    /// 是否 请求过新的一帧, 并清除 请求
+    #[inline]
+    pub fn take(&self) -> bool {
+        self.0.replace(false)
+    }
+}
+
+struct Playback {
+    animation: Animation,
+    state: PlayState,
+    /// 动画的 当前时间, 从 开始播放时 算起
+    current_time: Duration,
+    /// 上一帧 时钟的时间
+    last_now: Option<Duration>,
+    on_finish: Vec<Box<dyn FnMut()>>,
+    /// 渲染 这个动画的 App 的请求标记
+    frame_request: Option<FrameRequest>,
+}
+
+impl Playback {
+    /// 操作 在下一帧 生效, 请求 App 绘制
+    fn request_frame(&self) {
+        if let Some(frame_request) = &self.frame_request {
+            frame_request.request();
+        }
+    }
+}
+
+/// 控制 节点上的动画, 克隆后 控制同一个动画; 操作在 下一帧 生效
+#[derive(Clone)]
+pub struct AnimationHandle(Rc<RefCell<Playback>>);
+
+impl AnimationHandle {
+    pub fn new(animation: Animation) -> Self {
+        Self(Rc::new(RefCell::new(Playback {
+            animation,
+            state: PlayState::Running,
+            current_time: Duration::ZERO,
+            last_now: None,
+            on_finish: Vec::new(),
+            frame_request: None,
+        })))
+    }
+
+    /// 继续播放, 已结束 或 已取消的动画 从头开始
+    pub fn play(&self) {
+        let mut playback = self.0.borrow_mut();
+        if matches!(playback.state, PlayState::Finished | PlayState::Idle) {
+            playback.current_time = Duration::ZERO;
+        }
+        playback.state = PlayState::Running;
+        playback.request_frame();
+    }
+
+    pub fn pause(&self) {
+        let mut playback = self.0.borrow_mut();
+        if playback.state == PlayState::Running {
+            playback.state = PlayState::Paused;
+        }
+        playback.request_frame();
+    }
+
+    /// 跳转到 time, 跳回 已结束动画的 结束之前时 继续播放
+    pub fn seek(&self, time: Duration) {
+        let mut playback = self.0.borrow_mut();
+        playback.current_time = time;
+        if playback.state == PlayState::Finished && !playback.animation.is_finished(time) {
+            playback.state = PlayState::Running;
+        }
+        playback.request_frame();
+    }
+
+    /// 取消, 属性 恢复为 原来的值
+    pub fn cancel(&self) {
+        let mut playback = self.0.borrow_mut();
+        playback.state = PlayState::Idle;
+        playback.request_frame();
+    }
+
+    /// 动画 播放结束时 调用, 在 布局之前 的渲染树 没有被借用时 调用
+    pub fn on_finish(&self, on_finish: impl FnMut() + 'static) {
+        self.0.borrow_mut().on_finish.push(Box::new(on_finish));
+    }
+
+    #[inline]
+    pub fn state(&self) -> PlayState {
+        self.0.borrow().state
+    }
+
+    #[inline]
+    pub fn current_time(&self) -> Duration {
+        self.0.borrow().current_time
+    }
+
+    /// 之后的操作 请求 frame_request 绘制新的一帧
+    pub(crate) fn connect(&self, frame_request: &FrameRequest) {
+        self.0.borrow_mut().frame_request = Some(frame_request.clone());
+    }
+
+    /// 推进到 时钟时间 now, 返回 是否 刚刚播放结束
+    fn advance(&self, now: Duration) -> bool {
+        let mut playback = self.0.borrow_mut();
+        let delta = match playback.last_now {
+            Some(last) => now.saturating_sub(last),
+            None => Duration::ZERO,
+        };
+        playback.last_now = Some(now);
+        if playback.state != PlayState::Running {
+            return false;
+        }
+        playback.current_time += delta;
+        if playback.animation.is_finished(playback.current_time) {
+            playback.state = PlayState::Finished;
+            return true;
+        }
+        false
+    }
+
+    /// 当前时间 property 的值, 没有效果时 返回 None
+    fn value(
+        &self,
+        property: Property,
+        underlying: Option<AnimatedValue>,
+    ) -> Option<AnimatedValue> {
+        let playback = self.0.borrow();
+        if playback.state == PlayState::Idle {
+            return None;
+        }
+        let animation = &playback.animation;
+        let track = animation
+            .tracks
+            .iter()
+            .find(|track| track.property == property)?;
+        let progress = animation.progress(playback.current_time)?;
+        track.sample(progress, animation.easing, underlying)
+    }
+
+    /// 调用 完成回调, 回调中 可以再次操作 这个动画
+    pub(crate) fn notify_finished(&self) {
+        let mut callbacks = std::mem::take(&mut self.0.borrow_mut().on_finish);
+        for callback in &mut callbacks {
+            callback();
+        }
+        let mut playback = self.0.borrow_mut();
+        callbacks.append(&mut playback.on_finish);
+        playback.on_finish = callbacks;
+    }
+}
+
+impl From<Animation> for AnimationHandle {
+    fn from(animation: Animation) -> Self {
+        Self::new(animation)
+    }
+}
+
+impl PartialEq for AnimationHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for AnimationHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let playback = self.0.borrow();
+        f.debug_struct("AnimationHandle")
+            .field("animation", &playback.animation)
+            .field("state", &playback.state)
+            .field("current_time", &playback.current_time)
+            .finish()
+    }
+}
+
+/// 被动画的属性 原来的值 和 上一帧显示的值
+#[derive(Debug, Clone, PartialEq)]
+struct Underlying {
+    property: Property,
+    value: Option<AnimatedValue>,
+    shown: Option<AnimatedValue>,
+    captured: bool,
+}
+
+/// 节点上的动画
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Animations {
+    handles: Vec<AnimationHandle>,
+    underlying: Vec<Underlying>,
+    /// 层叠后 属性的值 都是 原来的值
+    retargeted: bool,
+}
+
+impl Animations {
+    #[inline]
+    pub(crate) fn handles(&self) -> &[AnimationHandle] {
+        &self.handles
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    pub(crate) fn add(&mut self, handle: AnimationHandle) {
+        for track in &handle.0.borrow().animation.tracks {
+            if self.underlying.iter().all(|u| u.property != track.property) {
+                self.underlying.push(Underlying {
+                    property: track.property,
+                    value: None,
+                    shown: None,
+                    captured: false,
+                });
+            }
+        }
+        self.handles.push(handle);
+    }
+
+    #[inline]
+    pub(crate) fn retarget(&mut self) {
+        self.retargeted = true;
+    }
+
+    /// 把 上一帧写入的 动画值 换回 原来的值; 属性 被改变过时, 改变后的值 是新的 原来的值
+    pub(crate) fn restore(&mut self, style: &mut Style) {
+        let retargeted = std::mem::take(&mut self.retargeted);
+        for underlying in &mut self.underlying {
+            let current = AnimatedValue::get(style, underlying.property);
+            if !underlying.captured || retargeted || current != underlying.shown {
+                underlying.value = current;
+                underlying.captured = true;
+            } else {
+                put(style, underlying.property, underlying.value);
+            }
+        }
+    }
+
+    /// 推进 所有动画到 now 并写入 style, 刚结束的动画 加入 finished; 返回 是否还有 正在播放的动画
+    pub(crate) fn apply(
+        &mut self,
+        style: &mut Style,
+        now: Duration,
+        finished: &mut Vec<AnimationHandle>,
+    ) -> bool {
+        let mut running = false;
+        for handle in &self.handles {
+            if handle.advance(now) {
+                finished.push(handle.clone());
+            }
+            running |= handle.state() == PlayState::Running;
+        }
+        for underlying in &mut self.underlying {
+            // 同一个属性 后添加的动画 优先
+            let value = self
+                .handles
+                .iter()
+                .rev()
+                .find_map(|handle| handle.value(underlying.property, underlying.value))
+                .or(underlying.value);
+            put(style, underlying.property, value);
+            underlying.shown = AnimatedValue::get(style, underlying.property);
+        }
+        running
+    }
+}
+
+/// 写入属性, None 清除 可选的属性
+fn put(style: &mut Style, property: Property, value: Option<AnimatedValue>) {
+    match value {
+        Some(value) => value.set(style, property),
+        None => match property {
+            Property::MinWidth => style.min_width = None,
+            Property::MaxWidth => style.max_width = None,
+            Property::MinHeight => style.min_height = None,
+            Property::MaxHeight => style.max_height = None,
+            Property::AspectRatio => style.aspect_ratio = None,
+            Property::FlexBasis => style.flex_basis = None,
+            Property::FontSize => style.font_size = None,
+            Property::LineHeight => style.line_height = None,
+            Property::Color => style.color = None,
+            Property::Top => style.top = None,
+            Property::Right => style.right = None,
+            Property::Bottom => style.bottom = None,
+            Property::Left => style.left = None,
+            _ => {}
+        },
+    }
+}
+
+#[test]
+fn test_animation() {
+    use crate::app::AppBuilder;
+    use crate::element::Element;
+    use crate::prelude::geom::color::Color;
+    use crate::{Background, Size};
+
+    let ms = Duration::from_millis;
+    let clock = ManualClock::default();
+    let pulse = AnimationHandle::new(
+        Animation::new(ms(100))
+            .keyframe(0.5, "width: 200; background-color: #000000")
+            .iterations(2.0)
+            .direction(Direction::Alternate),
+    );
+    let finished = Rc::new(Cell::new(0));
+    pulse.on_finish({
+        let finished = finished.clone();
+        move || finished.set(finished.get() + 1)
+    });
+    let grow = AnimationHandle::new(
+        Animation::new(ms(100))
+            .keyframe(0.0, "padding: 0")
+            .keyframe(1.0, "padding: 10")
+            .delay(ms(50))
+            .fill(FillMode::Forwards),
+    );
+    let element = Element::new("div").children(vec![
+        Element::new("pulse")
+            .width(100.0)
+            .background_color(Color::from("#ffffff"))
+            .animation(pulse.clone()),
+        Element::new("grow").animation(grow.clone()),
+    ]);
+    let mut app = AppBuilder::new(element).with_clock(clock.clone()).build();
+    let node = app.render_tree.first_child().unwrap();
+    let other = node.next_sibling().unwrap();
+    let width = || node.borrow().dom().style().width;
+    let padding = || other.borrow().dom().style().padding;
+
+    app.render();
+    assert!(app.is_animating());
+    assert_eq!(width(), Size::new_value(100.0));
+    // 没有 0 和 1 的关键帧: 从 原来的值 到 关键帧 再回到 原来的值
+    clock.advance(ms(25));
+    app.render();
+    assert_eq!(width(), Size::new_value(150.0));
+    assert_eq!(
+        node.borrow().dom().style().background,
+        Background::Color(Color::from("#808080"))
+    );
+    assert_eq!(node.borrow().dom().ava_box().width(), 150.0);
+    // 延迟期间 没有效果
+    assert_eq!(padding(), Default::default());
+
+    // 暂停时 时间不前进, 跳转 立即生效
+    pulse.pause();
+    clock.advance(ms(25));
+    app.render();
+    assert_eq!(width(), Size::new_value(150.0));
+    pulse.seek(ms(50));
+    app.render();
+    assert_eq!(width(), Size::new_value(200.0));
+    pulse.play();
+
+    // 第二次迭代 反向
+    clock.advance(ms(75));
+    app.render();
+    assert_eq!(pulse.current_time(), ms(125));
+    assert_eq!(width(), Size::new_value(150.0));
+    assert_eq!(padding(), crate::Quat::from("7.5"));
+
+    // 结束后 恢复为 原来的值, 调用 完成回调; fill forwards 保留 最后的值
+    clock.advance(ms(100));
+    app.render();
+    assert_eq!(pulse.state(), PlayState::Finished);
+    assert_eq!(grow.state(), PlayState::Finished);
+    assert_eq!(finished.get(), 1);
+    assert!(!app.is_animating());
+    assert_eq!(width(), Size::new_value(100.0));
+    assert_eq!(padding(), crate::Quat::from("10"));
+
+    // 播放中 改变 原来的值, 取消后 恢复为 新的值
+    pulse.play();
+    clock.advance(ms(25));
+    app.render();
+    let mut target = node.clone();
+    target.borrow_mut().dom_mut().set_width(300.0);
+    clock.advance(ms(25));
+    app.render();
+    assert_eq!(width(), Size::new_value(200.0));
+    pulse.cancel();
+    app.render();
+    assert_eq!(width(), Size::new_value(300.0));
+    assert!(!app.is_animating());
+
+    // 暂停 或 取消后 不再绘制, 句柄的操作 请求新的一帧
+    assert!(!app.take_frame_request());
+    pulse.play();
+    assert!(app.take_frame_request());
+    assert!(!app.take_frame_request());
+    app.render();
+    assert!(app.is_animating());
+    pulse.pause();
+    app.render();
+    assert!(!app.is_animating());
+    assert!(!app.take_frame_request());
+    pulse.seek(ms(50));
+    assert!(app.take_frame_request());
+
+    // 总时长 超出 Duration 的范围时 不会结束
+    let long = Animation::new(ms(100)).iterations(1e30);
+    assert_eq!(long.total_duration(), None);
+    assert!(!long.is_finished(Duration::MAX));
+}
@@ -1,7 +1,14 @@
+use std::rc::Rc;
+
+use crate::animation::{Clock, FrameRequest, SystemClock};
 use crate::element::*;
 use crate::painter::DummyPainter;
 // use crate::context::Context;
+use crate::layout::LayoutEngine;
 use crate::prelude::*;
+use crate::stylesheet::{self, StyleSheet};
+use crate::theme::Theme;
+use crate::PseudoClass;
 use crate::TreeNode;
 
 #[derive(Debug, Clone)]
@@ -35,151 +42,339 @@ pub struct App {
     pub data_tree: Option<TreeNode<DataObject>>,
     pub render_tree: TreeNode<Box<dyn RenderObject>>,
     pub painter: Box<dyn Painter>,
+    pub layout_engine: LayoutEngine,
+    /// 层叠到 渲染树上的 样式表
+    stylesheet: Option<StyleSheet>,
+    /// 层叠时 替换令牌的主题, 每个 App 各自的主题
+    theme: Rc<Theme>,
+    /// 正在拖拽滚动的节点, 以及 上一次的鼠标位置
+    scroll_drag: Option<(TreeNode<Box<dyn RenderObject>>, Vec2)>,
+    /// 处于 hover/active 状态的节点: 鼠标下 最内层的节点 和 它的祖先, 由内向外
+    hovered: Vec<TreeNode<Box<dyn RenderObject>>>,
+    active: Vec<TreeNode<Box<dyn RenderObject>>>,
+    /// 获得焦点的节点
+    focused: Option<TreeNode<Box<dyn RenderObject>>>,
+    /// 布局没有改变 也需要重新绘制, 如: 第一次绘制, 窗口尺寸改变
+    repaint: bool,
+    /// 过渡和动画 使用的时钟
+    clock: Box<dyn Clock>,
+    /// 上一帧后 还有 正在进行的过渡, 需要继续绘制下一帧
+    animating: bool,
+    /// 动画句柄的操作 请求的新帧
+    frame_request: FrameRequest,
 }
 
 impl App {
     pub fn render(&mut self) {
         let App {
-            event_tree,
-            data_tree,
             render_tree,
             painter,
+            layout_engine,
+            repaint,
+            clock,
+            animating,
+            frame_request,
+            ..
         } = self;
 
         let window_size = painter.size();
+        layout_engine.set_scale_factor(painter.scale_factor());
+        layout_engine.set_text_measurer(painter.text_measurer());
+
         /*
-            根节点布局, 强制到 屏幕尺寸, 且 块布局
+            推进过渡和动画, 插值结果 写入样式 并标记为 dirty; 渲染树 不再被借用后 调用 完成回调;
+            这一帧 满足了 之前的请求, 完成回调中的操作 再次请求
         */
-        {
-            let mut root_node = render_tree.borrow_mut();
-            let dom = root_node.dom_mut();
-            dom.set_style(Style::default());
-            dom.set_layout(Layout::Block);
-            dom.set_width(window_size[0]);
-            dom.set_height(window_size[1]);
-            dom.set_ava_box(Box2::new(vec2(0.0, 0.0), window_size.into()));
+        let now = clock.now();
+        let mut finished = Vec::new();
+        *animating = false;
+        frame_request.take();
+        for mut node in render_tree.descendants() {
+            let mut node = node.borrow_mut();
+            let dom = node.dom_mut();
+            for handle in dom.animations() {
+                handle.connect(frame_request);
+            }
+            *animating |= dom.update_animations(now, &mut finished);
+        }
+        for handle in finished {
+            handle.notify_finished();
         }
 
         /*
-            执行布局
+            执行布局, 只重新计算 改变的节点
         */
+        let changed = layout_engine.compute(render_tree, window_size.into());
+        if changed.is_empty() && !*repaint {
+            return;
+        }
+        *repaint = false;
 
-        for node in render_tree.traverse() {
-            match node {
-                TreeNodeEdge::Start(mut node) => {
-                    /*
-                        根据当前节点多的布局, 计算并更新 所有子节点的布局
-                    */
-                    let dom = node.borrow().dom().clone();
-                    let parent_size = dom.size();
-
-                    // 在一个布局中, Cursor移动, 用于记录下一个 布局内节点 的起始位置, 初始位置是 父节点的area
-                    let mut cursor = dom.ava_box();
-
-                    match dom.layout() {
-                        Layout::Inline => {}
-                        Layout::InlineBlock => {}
-                        Layout::Block => {
-                            // 当前节点是Block, 则子节点的最大宽度和最大高度是确定的: 更新子节点的 宽度/高度/Area/父节点尺寸
-                            for mut child in node.children() {
-                                let mut child = child.borrow_mut();
-                                let cdom = child.dom_mut();
-
-                                // 更新盒子轮廓
-                                cdom.set_margin(cdom.margin().update(parent_size));
-                                cdom.set_padding(cdom.padding().update(parent_size));
-                                cdom.set_border_width(cdom.border_width().update(parent_size));
-
-                                // 当前节点的宽度 = 父节点的宽度 - 当前节点的边沿宽度
-                                cdom.set_width(parent_size.x - cdom.edge_width());
-                                // 根据父节点高度 计算当前节点的高度
-                                cdom.update_height(parent_size.y);
-                                // cdom.set_height();
-
-                                // 更新有效区域
-                                let start_point = cursor.min + cdom.left_top();
-                                let end_point = start_point + vec2(cdom.width(), cdom.height());
-                                cdom.set_ava_box(box2(start_point, end_point));
-
-                                /*
-                                    更新Cursor
-                                */
-                                // cursor向下移动
-                                cursor.min.y += cdom.box_height();
-                            }
-                        }
-                        Layout::RowFlex => {}
-                        Layout::ColFlex => {}
+        /*
+            绘制, 根节点 不绘制
+            overflow 不是 Visible 的节点, 子级 裁剪到 它的内边距盒子 (和祖先的裁剪区域 取交集)
+        */
+        let mut clips: Vec<Box2> = Vec::new();
+        for edge in render_tree.traverse() {
+            match edge {
+                TreeNodeEdge::Start(node) => {
+                    if node.parent().is_some() {
+                        node.clone().borrow_mut().paint(painter);
                     }
 
-                    let mut parent = node.parent();
-                    if parent.is_none() {
-                        continue;
+                    let padding_box = {
+                        let node = node.borrow();
+                        let dom = node.dom();
+                        dom.style().overflow.is_clip().then(|| dom.padding_box())
+                    };
+                    if let Some(padding_box) = padding_box {
+                        let clip = match clips.last() {
+                            Some(last) => {
+                                last.intersection(&padding_box).unwrap_or_else(Box2::zero)
+                            }
+                            None => padding_box,
+                        };
+                        clips.push(clip);
+                        painter.clip(Some(clip));
                     }
-                    let parent_node = parent.as_mut().unwrap().borrow_mut();
-                    let mut node = node.borrow_mut();
-                    let parent_dom = parent_node.dom();
-                    let dom = node.dom_mut();
-                    println!(
-                        "<{:?}> -- node_id: {:?} width: {:?} height: {:?}",
-                        dom.tag(),
-                        dom.node_id(),
-                        dom.width(),
-                        dom.height()
-                    );
-                    node.layout(parent_dom);
-                    node.paint(painter);
                 }
                 TreeNodeEdge::End(node) => {
-                    // let node = node.borrow();
-                    // let dom = node.dom();
-                    // println!("</{}>: {:?}", dom.tag(), node.node_id());
+                    if node.borrow().dom().style().overflow.is_clip() {
+                        clips.pop();
+                        painter.clip(clips.last().copied());
+                    }
                 }
             }
         }
+        // 提交 这一帧的 绘制命令
+        painter.render();
+    }
 
-        // 遍历事件树
-        if let Some(event_tree) = event_tree {
-            for node in event_tree.traverse() {
-                match node {
-                    TreeNodeEdge::Start(node) => {
-                        let node = node.borrow();
-                        println!(
-                            "event node -- node_id: {:?} event: {:?}",
-                            &node.node.borrow().node_id(),
-                            &node.event
-                        );
-                    }
-                    _ => {}
-                }
+    /// 替换样式表, 重新层叠 整棵渲染树, 样式改变的节点 在下一次 render 时 重新布局
+    pub fn set_stylesheet(&mut self, stylesheet: StyleSheet) {
+        stylesheet.apply(&self.render_tree, &self.theme);
+        self.stylesheet = Some(stylesheet);
+    }
+
+    /// 切换主题, 重新解析 所有节点引用的令牌, 样式改变的节点 在下一次 render 时 重新布局
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = Rc::new(theme);
+        stylesheet::cascade(self.stylesheet.as_ref(), &self.theme, &self.render_tree);
+        self.repaint = true;
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Rc<Theme> {
+        self.theme.clone()
+    }
+
+    /// 设置节点的交互状态, 状态改变时 重新层叠 节点和它的后代, 返回 是否改变
+    pub fn set_state(
+        &mut self,
+        node: &TreeNode<Box<dyn RenderObject>>,
+        class: PseudoClass,
+        on: bool,
+    ) -> bool {
+        let mut node = node.clone();
+        let changed = node.borrow_mut().dom_mut().set_state(class, on);
+        if changed {
+            stylesheet::cascade(self.stylesheet.as_ref(), &self.theme, &node);
+        }
+        changed
+    }
+
+    #[inline]
+    pub fn stylesheet(&self) -> Option<&StyleSheet> {
+        self.stylesheet.as_ref()
+    }
+
+    /// 还有 正在进行的过渡, 窗口应该 继续请求重绘
+    #[inline]
+    pub fn is_animating(&self) -> bool {
+        self.animating
+    }
+
+    /// 上一帧后 动画句柄 被操作过 (如 暂停后 继续播放 或 跳转), 窗口应该 请求重绘; 检查后 清除
+    #[inline]
+    pub fn take_frame_request(&self) -> bool {
+        self.frame_request.take()
+    }
+
+    pub fn resize(&mut self, size: [f32; 2]) {
+        self.painter.resize(size);
+        self.repaint = true;
+    }
+
+    /// 窗口的缩放比例改变, dp 单位 需要重新布局
+    pub fn rescale(&mut self, scale_factor: f32) {
+        self.painter.rescale(scale_factor);
+        self.repaint = true;
+    }
+
+    /// 鼠标滚轮: 滚动 鼠标位置下 最内层的 可滚动节点, 不能继续滚动时 交给祖先节点;
+    /// delta 是 滚动偏移量的变化, 返回 是否需要重绘
+    pub fn scroll(&mut self, pos: Vec2, delta: Vec2) -> bool {
+        let target = match self.scroll_target(pos) {
+            Some(target) => target,
+            None => return false,
+        };
+        for mut node in target.ancestors() {
+            if !node.borrow().dom().style().overflow.is_scrollable() {
+                continue;
+            }
+            if node.borrow_mut().dom_mut().scroll_by(delta) {
+                return true;
             }
         }
+        false
+    }
 
-        // 遍历数据树
-        if let Some(data_tree) = data_tree {
-            for node in data_tree.traverse() {
-                match node {
-                    TreeNodeEdge::Start(node) => {
-                        let node = node.borrow();
-                        println!(
-                            "data node -- node_id: {:?} data: {:?}",
-                            &node.node.borrow().node_id(),
-                            &node.data
-                        );
+    /// 鼠标按下: 开始拖拽 鼠标位置下 最内层的 可滚动节点;
+    /// 鼠标下的节点 和 它的祖先 进入 active 状态, 最内层的节点 获得焦点, 返回 是否需要重绘
+    pub fn pointer_down(&mut self, pos: Vec2) -> bool {
+        self.scroll_drag = self.scroll_target(pos).map(|node| (node, pos));
+
+        let target = self.node_at(pos).filter(|node| !is_disabled(node));
+        let active = target
+            .iter()
+            .flat_map(|node| node.ancestors())
+            .filter(|node| !is_disabled(node))
+            .collect::<Vec<_>>();
+        let mut changed = false;
+        for node in &active {
+            changed |= self.set_state(node, PseudoClass::Active, true);
+        }
+        self.active = active;
+        if self.focused != target {
+            if let Some(focused) = self.focused.take() {
+                changed |= self.set_state(&focused, PseudoClass::Focus, false);
+            }
+            if let Some(target) = &target {
+                changed |= self.set_state(target, PseudoClass::Focus, true);
+            }
+            self.focused = target;
+        }
+        changed
+    }
+
+    /// 鼠标移动: 拖拽时 内容跟随鼠标移动, 更新 hover 状态, 返回 是否需要重绘
+    pub fn pointer_move(&mut self, pos: Vec2) -> bool {
+        let scrolled = match &mut self.scroll_drag {
+            Some((node, last)) => {
+                let delta = *last - pos;
+                *last = pos;
+                node.borrow_mut().dom_mut().scroll_by(delta)
+            }
+            None => false,
+        };
+        self.update_hover(pos) || scrolled
+    }
+
+    /// 鼠标松开: 结束拖拽, 鼠标下的节点 和 它的祖先 由内向外 收到 MouseUp, 退出 active 状态;
+    /// 返回 是否需要重绘
+    pub fn pointer_up(&mut self, pos: Vec2) -> bool {
+        self.scroll_drag = None;
+        if let Some(target) = self.node_at(pos) {
+            for node in target.ancestors() {
+                self.dispatch(&node, |event| {
+                    if let Some(onmouseup) = event.onmouseup.as_mut() {
+                        onmouseup(MouseUp {});
                     }
-                    _ => {}
+                });
+            }
+        }
+        let mut changed = false;
+        for node in std::mem::take(&mut self.active) {
+            changed |= self.set_state(&node, PseudoClass::Active, false);
+        }
+        changed
+    }
+
+    /// 鼠标离开的节点 由内向外 收到 MouseLeave, 鼠标进入的节点 由外向内 收到 MouseEnter;
+    /// 返回 hover 状态 是否改变
+    fn update_hover(&mut self, pos: Vec2) -> bool {
+        let hovered = self
+            .node_at(pos)
+            .map(|node| node.ancestors().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let old = std::mem::replace(&mut self.hovered, hovered.clone());
+        let mut changed = false;
+        for node in old.iter().filter(|node| !hovered.contains(node)) {
+            changed |= self.set_state(node, PseudoClass::Hover, false);
+            self.dispatch(node, |event| {
+                if let Some(onmouseleave) = event.onmouseleave.as_mut() {
+                    onmouseleave(MouseLeave {});
+                }
+            });
+        }
+        for node in hovered.iter().rev().filter(|node| !old.contains(node)) {
+            changed |= self.set_state(node, PseudoClass::Hover, true);
+            self.dispatch(node, |event| {
+                if let Some(onmouseenter) = event.onmouseenter.as_mut() {
+                    onmouseenter(MouseEnter {});
+                }
+            });
+        }
+        changed
+    }
+
+    /// 调用 node 的事件监听器, 禁用的节点 不接收事件
+    fn dispatch(&self, node: &TreeNode<Box<dyn RenderObject>>, f: impl Fn(&mut EventListener)) {
+        if is_disabled(node) {
+            return;
+        }
+        if let Some(event_tree) = &self.event_tree {
+            for mut object in event_tree.descendants() {
+                if object.borrow().node == *node {
+                    f(&mut object.borrow_mut().event);
                 }
             }
         }
     }
-    pub fn resize(&mut self, size: [f32; 2]) {
-        self.painter.resize(size);
+
+    /// 鼠标位置下 最内层的节点, 被祖先节点 裁剪掉的部分 不算
+    fn node_at(&self, pos: Vec2) -> Option<TreeNode<Box<dyn RenderObject>>> {
+        self.render_tree
+            .descendants()
+            .filter(|node| node.borrow().dom().padding_box().contains(pos))
+            .filter(|node| is_visible(node, pos))
+            .last()
+    }
+
+    /// 鼠标位置下 最内层的 可滚动节点, 被祖先节点 裁剪掉的部分 不算
+    fn scroll_target(&self, pos: Vec2) -> Option<TreeNode<Box<dyn RenderObject>>> {
+        self.render_tree
+            .descendants()
+            .filter(|node| {
+                let node = node.borrow();
+                let dom = node.dom();
+                dom.style().overflow.is_scrollable() && dom.padding_box().contains(pos)
+            })
+            .filter(|node| is_visible(node, pos))
+            .last()
     }
 }
 
+/// pos 没有被 node 的祖先节点 裁剪掉
+fn is_visible(node: &TreeNode<Box<dyn RenderObject>>, pos: Vec2) -> bool {
+    node.ancestors().skip(1).all(|ancestor| {
+        let ancestor = ancestor.borrow();
+        let dom = ancestor.dom();
+        !dom.style().overflow.is_clip() || dom.padding_box().contains(pos)
+    })
+}
+
+fn is_disabled(node: &TreeNode<Box<dyn RenderObject>>) -> bool {
+    node.borrow().dom().state().contains(PseudoClass::Disabled)
+}
+
 pub struct AppBuilder {
     element: Element,
     painter: Box<dyn Painter>,
+    layout_engine: LayoutEngine,
+    stylesheet: Option<StyleSheet>,
+    theme: Option<Theme>,
+    clock: Box<dyn Clock>,
 }
 
 impl AppBuilder {
@@ -187,6 +382,10 @@ impl AppBuilder {
         Self {
             element: b,
             painter: Box::new(DummyPainter::default()),
+            layout_engine: LayoutEngine::default(),
+            stylesheet: None,
+            theme: None,
+            clock: Box::new(SystemClock::default()),
         }
     }
 
@@ -195,16 +394,158 @@ impl AppBuilder {
         self
     }
 
+    pub fn with_layout_engine(mut self, layout_engine: LayoutEngine) -> Self {
+        self.layout_engine = layout_engine;
+        self
+    }
+
+    pub fn with_stylesheet(mut self, stylesheet: StyleSheet) -> Self {
+        self.stylesheet = Some(stylesheet);
+        self
+    }
+
+    /// 初始主题, 默认是 浅色主题
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// 过渡和动画 使用的时钟, 默认是 系统时钟; 测试时 可以使用 ManualClock
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
     pub fn build(self) -> App {
-        let Self { element, painter } = self;
+        let Self {
+            element,
+            painter,
+            layout_engine,
+            stylesheet,
+            theme,
+            clock,
+        } = self;
+        let theme = Rc::new(theme.unwrap_or_else(Theme::light));
         let (render_tree, event_tree, data_tree) = element.build();
+        // 没有样式表时 也需要层叠 节点的状态样式
+        stylesheet::cascade(stylesheet.as_ref(), &theme, &render_tree);
         App {
             render_tree,
             event_tree,
             data_tree,
             painter,
+            layout_engine,
+            stylesheet,
+            theme,
+            scroll_drag: None,
+            hovered: Vec::new(),
+            active: Vec::new(),
+            focused: None,
+            repaint: true,
+            clock,
+            animating: false,
+            frame_request: FrameRequest::default(),
         }
     }
 }
 
 // WindowBuilder
+
+#[test]
+fn test_pseudo_class_states() {
+    use std::cell::Cell;
+
+    use crate::Background;
+
+    let entered = Rc::new(Cell::new(0));
+    let left = Rc::new(Cell::new(0));
+    let released = Rc::new(Cell::new(0));
+    let element = Element::new("div").children(vec![
+        Element::new("button")
+            .class("btn")
+            .width(100.0)
+            .height(40.0)
+            .hover("width: 120")
+            .onmouseenter({
+                let entered = entered.clone();
+                move |_| entered.set(entered.get() + 1)
+            })
+            .onmouseleave({
+                let left = left.clone();
+                move |_| left.set(left.get() + 1)
+            })
+            .onmouseup({
+                let released = released.clone();
+                move |_| released.set(released.get() + 1)
+            }),
+        Element::new("button")
+            .class("btn")
+            .width(100.0)
+            .height(40.0)
+            .disabled(true)
+            .onmouseenter({
+                let entered = entered.clone();
+                move |_| entered.set(entered.get() + 1)
+            }),
+    ]);
+    let stylesheet = StyleSheet::parse(
+        ".btn:hover { background-color: #ff0000; }
+        .btn:active { height: 50; }
+        .btn:focus { border-width: 2; }
+        .btn:disabled { background-color: #808080; }",
+    )
+    .unwrap();
+    let mut app = AppBuilder::new(element).with_stylesheet(stylesheet).build();
+    app.render();
+    let button = app.render_tree.first_child().unwrap();
+    let disabled = button.next_sibling().unwrap();
+    let style = |node: &TreeNode<Box<dyn RenderObject>>| node.borrow().dom().style().clone();
+    assert_eq!(
+        style(&disabled).background,
+        Background::Color(Color::from("#808080"))
+    );
+
+    // 进入: 状态样式 优先于 样式表
+    assert!(app.pointer_move(vec2(10.0, 10.0)));
+    app.render();
+    assert_eq!(button.borrow().dom().width(), 120.0);
+    assert_eq!(style(&button).background, Background::Color(Color::RED));
+    assert_eq!(entered.get(), 1);
+    assert!(!app.pointer_move(vec2(20.0, 10.0)));
+    assert_eq!(entered.get(), 1);
+
+    // 按下 和 松开
+    assert!(app.pointer_down(vec2(20.0, 10.0)));
+    assert_eq!(style(&button).height, 50.0.into());
+    assert_eq!(style(&button).border_width, "2".into());
+    assert!(app.pointer_up(vec2(20.0, 10.0)));
+    assert_eq!(released.get(), 1);
+    assert_eq!(style(&button).height, 40.0.into());
+    assert_eq!(style(&button).border_width, "2".into());
+    app.render();
+
+    // 禁用的节点: 匹配 hover, 但不接收事件, 不能激活 和 获得焦点
+    assert!(app.pointer_move(vec2(10.0, 60.0)));
+    assert_eq!(left.get(), 1);
+    assert_eq!(entered.get(), 1);
+    assert_eq!(style(&button).width, 100.0.into());
+    assert!(disabled.borrow().dom().state().contains(PseudoClass::Hover));
+    assert!(app.pointer_down(vec2(10.0, 60.0)));
+    assert!(!disabled
+        .borrow()
+        .dom()
+        .state()
+        .contains(PseudoClass::Active));
+    assert!(!button.borrow().dom().state().contains(PseudoClass::Focus));
+
+    // 程序中 修改的属性 不会被 之后的层叠 撤销, 状态样式 仍然优先
+    let mut target = button.clone();
+    target.borrow_mut().dom_mut().set_width(300.0);
+    target.borrow_mut().dom_mut().set_height(60.0);
+    assert!(app.pointer_move(vec2(10.0, 10.0)));
+    assert_eq!(style(&button).width, 120.0.into());
+    assert_eq!(style(&button).height, 60.0.into());
+    assert!(app.pointer_move(vec2(10.0, 300.0)));
+    assert_eq!(style(&button).width, 300.0.into());
+    assert_eq!(style(&button).height, 60.0.into());
+}
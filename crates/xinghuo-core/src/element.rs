@@ -1,12 +1,22 @@
-use std::{any::Any, fmt::Debug};
+use std::{any::Any, cell::RefCell, fmt::Debug, rc::Rc};
 
 use geom::color::Color;
 
 use crate::{
-    event::{Click, EventListener},
+    animation::AnimationHandle,
+    event::{Click, EventListener, MouseEnter, MouseLeave, MouseUp},
     id::Id,
+    layout::IntrinsicSize,
     prelude::*,
-    Convert, DomElement, Layout, Size,
+    stylesheet::{parse_declarations, Property},
+    text::{
+        current_measurer, layout_spans, layout_text, measure_spans, measure_text, measurer_key,
+        FontStyle, FontWeight, Span, TextLayout, TextStyle,
+    },
+    transition::Transition,
+    AlignItems, Anchor, Background, BoxShadow, BoxSizing, Convert, DomElement, FlexWrap,
+    GridPlacement, GridTemplate, JustifyContent, Layout, Overflow, Position, PseudoClass, Quat,
+    Size, TextAlign, VerticalAlign,
 };
 
 pub struct LayoutCx {
@@ -32,27 +42,52 @@ pub struct EventObject {
 }
 
 pub trait RenderObject: Debug {
-    /// 根据当前节点的布局, 计算子节点布局,
-    fn layout(&mut self, parent: &DomElement) {
-        let dom = self.dom_mut();
-        if dom.is_dirty() {
-            // 布局
-            match parent.layout() {
-                Layout::Inline => {}
-                Layout::InlineBlock => {}
-                Layout::Block => {}
-                Layout::RowFlex => todo!(),
-                Layout::ColFlex => todo!(),
-            }
-            dom.set_dirty(false);
-        }
+    /// 自身内容 (不包括子级和边沿) 的内在宽度, 如文本; 默认没有内容
+    fn intrinsic_width(&self) -> IntrinsicSize {
+        IntrinsicSize::default()
     }
 
+    /// 内容宽度为 width 时, 自身内容 (不包括子级和边沿) 的高度
+    fn content_height(&self, _width: f32) -> f32 {
+        0.0
+    }
+
+    /// 先绘制 外阴影, 再绘制 背景, 最后绘制 内阴影; 阴影 按列表的逆序绘制, 前面的在上层;
+    /// 背景 和 阴影 使用 相同的圆角
     fn paint(&mut self, painter: &mut Box<dyn Painter>) {
         let dom = self.dom();
-        let rect = &[Quad::new(dom.ava_box().to_rect(), dom.background_color())];
-        println!("paint rect {:?}", rect);
-        painter.rect(rect);
+        let area = dom.ava_box().to_rect();
+        let resolve = |size: Size| {
+            dom.with_units(|| size.resolve(area.size.min_element()))
+                .unwrap_or(0.0)
+        };
+        let radius = resolve(dom.border_radius().top());
+        let border_width = resolve(dom.border_width().top());
+        let (inset, outset): (Vec<_>, Vec<_>) = dom
+            .box_shadow()
+            .iter()
+            .rev()
+            .map(|shadow| shadow.to_shadow(area, radius))
+            .partition(Shadow::is_inset);
+        if !outset.is_empty() {
+            painter.shadow(&outset);
+        }
+        match dom.background() {
+            Background::Color(color) => {
+                let rect = &[Quad {
+                    border_color: dom.border_color().to_value(),
+                    border_radius: radius,
+                    border_width,
+                    ..Quad::new(area, *color)
+                }];
+                println!("paint rect {:?}", rect);
+                painter.rect(rect);
+            }
+            Background::Gradient(gradient) => painter.gradient(area, radius, gradient),
+        }
+        if !inset.is_empty() {
+            painter.shadow(&inset);
+        }
     }
 
     fn dom(&self) -> &DomElement;
@@ -67,6 +102,10 @@ pub struct Element {
     pub event: Option<EventListener>,
     pub data: Option<Box<dyn Any>>,
     pub children: Vec<Element>,
+    /// 文本节点的内容, 有内容时 渲染对象是 Text
+    pub text: Option<String>,
+    /// 富文本节点的片段, 有片段时 渲染对象是 RichText
+    pub spans: Option<Vec<Span>>,
 }
 
 impl Element {
@@ -77,9 +116,80 @@ impl Element {
             event: None,
             data: None,
             children: Vec::new(),
+            text: None,
+            spans: None,
         }
     }
 
+    /// 文本节点, 作为 行内块 参与布局
+    pub fn text(content: impl Into<String>) -> Self {
+        let mut element = Self::new("text");
+        element.dom.set_layout(Layout::InlineBlock);
+        element.text = Some(content.into());
+        element
+    }
+
+    /// 富文本节点, 所有片段 作为一个段落 换行
+    pub fn rich_text<S: Into<Span>>(spans: impl IntoIterator<Item = S>) -> Self {
+        let mut element = Self::new("rich-text");
+        element.dom.set_layout(Layout::InlineBlock);
+        element.spans = Some(spans.into_iter().map(Into::into).collect());
+        element
+    }
+
+    pub fn id(mut self, id: impl ToString) -> Self {
+        self.dom.set_id(id);
+        self
+    }
+
+    /// 以空白分隔的 类名列表, 用于 样式表的选择器
+    pub fn class(mut self, class: &str) -> Self {
+        self.dom.set_class(class);
+        self
+    }
+
+    /// 内联声明, 如: "background-color: var(--color-primary); padding: var(--spacing-md)"
+    pub fn inline_style(mut self, declarations: &str) -> Self {
+        self.dom.set_inline_style(parse_declarations(declarations));
+        self
+    }
+
+    /// 处于 class 状态时 应用的声明, 如: "background-color: #ff0000; width: 100"
+    pub fn state_style(mut self, class: PseudoClass, declarations: &str) -> Self {
+        self.dom
+            .set_state_style(class, parse_declarations(declarations));
+        self
+    }
+
+    pub fn hover(self, declarations: &str) -> Self {
+        self.state_style(PseudoClass::Hover, declarations)
+    }
+
+    pub fn focus(self, declarations: &str) -> Self {
+        self.state_style(PseudoClass::Focus, declarations)
+    }
+
+    pub fn active(self, declarations: &str) -> Self {
+        self.state_style(PseudoClass::Active, declarations)
+    }
+
+    /// property 改变时的过渡, 如: .transition(Property::BackgroundColor, Transition::new(ms, Easing::EASE))
+    pub fn transition(mut self, property: Property, transition: Transition) -> Self {
+        self.dom.set_transition(property, transition);
+        self
+    }
+
+    /// 关键帧动画, 传入 AnimationHandle 的克隆 可以在之后 控制动画
+    pub fn animation(mut self, animation: impl Into<AnimationHandle>) -> Self {
+        self.dom.add_animation(animation);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.dom.set_state(PseudoClass::Disabled, disabled);
+        self
+    }
+
     pub fn onclick(mut self, onclick: impl FnMut(Click) + 'static) -> Self {
         if self.event.is_none() {
             self.event = Some(EventListener::default());
@@ -90,6 +200,36 @@ impl Element {
         self
     }
 
+    pub fn onmouseenter(mut self, onmouseenter: impl FnMut(MouseEnter) + 'static) -> Self {
+        if self.event.is_none() {
+            self.event = Some(EventListener::default());
+        }
+        if let Some(event) = self.event.as_mut() {
+            event.onmouseenter = Some(Box::new(onmouseenter));
+        }
+        self
+    }
+
+    pub fn onmouseleave(mut self, onmouseleave: impl FnMut(MouseLeave) + 'static) -> Self {
+        if self.event.is_none() {
+            self.event = Some(EventListener::default());
+        }
+        if let Some(event) = self.event.as_mut() {
+            event.onmouseleave = Some(Box::new(onmouseleave));
+        }
+        self
+    }
+
+    pub fn onmouseup(mut self, onmouseup: impl FnMut(MouseUp) + 'static) -> Self {
+        if self.event.is_none() {
+            self.event = Some(EventListener::default());
+        }
+        if let Some(event) = self.event.as_mut() {
+            event.onmouseup = Some(Box::new(onmouseup));
+        }
+        self
+    }
+
     pub fn width(mut self, width: impl Into<Size>) -> Self {
         self.dom.set_width(width);
         self
@@ -100,19 +240,226 @@ impl Element {
         self
     }
 
+    pub fn box_sizing(mut self, box_sizing: BoxSizing) -> Self {
+        self.dom.set_box_sizing(box_sizing);
+        self
+    }
+
+    pub fn aspect_ratio(mut self, aspect_ratio: f32) -> Self {
+        self.dom.set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    pub fn min_width(mut self, min_width: impl Into<Size>) -> Self {
+        self.dom.set_min_width(min_width);
+        self
+    }
+
+    pub fn max_width(mut self, max_width: impl Into<Size>) -> Self {
+        self.dom.set_max_width(max_width);
+        self
+    }
+
+    pub fn min_height(mut self, min_height: impl Into<Size>) -> Self {
+        self.dom.set_min_height(min_height);
+        self
+    }
+
+    pub fn max_height(mut self, max_height: impl Into<Size>) -> Self {
+        self.dom.set_max_height(max_height);
+        self
+    }
+
+    pub fn padding(mut self, padding: impl Into<Quat>) -> Self {
+        self.dom.set_padding(padding);
+        self
+    }
+
+    pub fn margin(mut self, margin: impl Into<Quat>) -> Self {
+        self.dom.set_margin(margin);
+        self
+    }
+
+    pub fn border_width(mut self, border_width: impl Into<Quat>) -> Self {
+        self.dom.set_border_width(border_width);
+        self
+    }
+
     pub fn background_color(mut self, background_color: impl Into<Color>) -> Self {
         self.dom.set_background_color(background_color);
         self
     }
 
+    /// 纯色 或 渐变, 如: "linear-gradient(135deg, #4096ff, #1677ff 60%, #0958d9)"
+    pub fn background(mut self, background: impl Into<Background>) -> Self {
+        self.dom.set_background(background);
+        self
+    }
+
+    /// 如: "0 2 8 #00000026, inset 0 1 0 #ffffff80"
+    pub fn box_shadow(mut self, box_shadow: &str) -> Self {
+        self.dom.set_box_shadow(BoxShadow::parse_list(box_shadow));
+        self
+    }
+
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        self.dom.set_font_size(font_size);
+        self
+    }
+
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.dom.set_line_height(line_height);
+        self
+    }
+
+    pub fn font_family(mut self, font_family: impl ToString) -> Self {
+        self.dom.set_font_family(font_family);
+        self
+    }
+
+    pub fn font_weight(mut self, font_weight: impl Into<FontWeight>) -> Self {
+        self.dom.set_font_weight(font_weight);
+        self
+    }
+
+    pub fn font_style(mut self, font_style: impl Into<FontStyle>) -> Self {
+        self.dom.set_font_style(font_style);
+        self
+    }
+
+    pub fn color(mut self, color: impl Into<Color>) -> Self {
+        self.dom.set_color(color);
+        self
+    }
+
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.dom.set_layout(layout);
+        self
+    }
+
+    pub fn flex_grow(mut self, flex_grow: f32) -> Self {
+        self.dom.set_flex_grow(flex_grow);
+        self
+    }
+
+    pub fn flex_shrink(mut self, flex_shrink: f32) -> Self {
+        self.dom.set_flex_shrink(flex_shrink);
+        self
+    }
+
+    pub fn flex_basis(mut self, flex_basis: impl Into<Size>) -> Self {
+        self.dom.set_flex_basis(flex_basis);
+        self
+    }
+
+    pub fn flex_wrap(mut self, flex_wrap: FlexWrap) -> Self {
+        self.dom.set_flex_wrap(flex_wrap);
+        self
+    }
+
+    pub fn justify_content(mut self, justify_content: JustifyContent) -> Self {
+        self.dom.set_justify_content(justify_content);
+        self
+    }
+
+    pub fn align_items(mut self, align_items: AlignItems) -> Self {
+        self.dom.set_align_items(align_items);
+        self
+    }
+
+    pub fn align_self(mut self, align_self: AlignItems) -> Self {
+        self.dom.set_align_self(align_self);
+        self
+    }
+
+    pub fn gap(mut self, gap: impl Into<Size>) -> Self {
+        self.dom.set_gap(gap);
+        self
+    }
+
+    pub fn grid_template_columns(mut self, columns: impl Into<GridTemplate>) -> Self {
+        self.dom.set_grid_template_columns(columns);
+        self
+    }
+
+    pub fn grid_template_rows(mut self, rows: impl Into<GridTemplate>) -> Self {
+        self.dom.set_grid_template_rows(rows);
+        self
+    }
+
+    pub fn grid_column(mut self, column: impl Into<GridPlacement>) -> Self {
+        self.dom.set_grid_column(column);
+        self
+    }
+
+    pub fn grid_row(mut self, row: impl Into<GridPlacement>) -> Self {
+        self.dom.set_grid_row(row);
+        self
+    }
+
+    pub fn vertical_align(mut self, vertical_align: VerticalAlign) -> Self {
+        self.dom.set_vertical_align(vertical_align);
+        self
+    }
+
+    pub fn text_align(mut self, text_align: TextAlign) -> Self {
+        self.dom.set_text_align(text_align);
+        self
+    }
+
+    pub fn stack_align(mut self, stack_align: Anchor) -> Self {
+        self.dom.set_stack_align(stack_align);
+        self
+    }
+
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.dom.set_anchor(anchor);
+        self
+    }
+
+    pub fn stretch(mut self, stretch: bool) -> Self {
+        self.dom.set_stretch(stretch);
+        self
+    }
+
+    pub fn position(mut self, position: Position) -> Self {
+        self.dom.set_position(position);
+        self
+    }
+
+    pub fn top(mut self, top: impl Into<Size>) -> Self {
+        self.dom.set_top(top);
+        self
+    }
+
+    pub fn right(mut self, right: impl Into<Size>) -> Self {
+        self.dom.set_right(right);
+        self
+    }
+
+    pub fn bottom(mut self, bottom: impl Into<Size>) -> Self {
+        self.dom.set_bottom(bottom);
+        self
+    }
+
+    pub fn left(mut self, left: impl Into<Size>) -> Self {
+        self.dom.set_left(left);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.dom.set_overflow(overflow);
+        self
+    }
+
     pub fn data(mut self, data: impl Any) -> Self {
         let data: Box<dyn Any> = Box::new(data);
         self.data = Some(data);
         self
     }
 
-    pub fn child(mut self, child: Self) -> Self {
-        self.children.push(child);
+    pub fn child(mut self, child: impl Into<Self>) -> Self {
+        self.children.push(child.into());
         self
     }
 
@@ -133,12 +480,18 @@ impl Element {
             event,
             data,
             children,
+            text,
+            spans,
         } = self;
 
         // let node_id = dom.node_id();
 
         // 渲染节点
-        let render_object: Box<dyn RenderObject> = Box::new(Node { dom });
+        let render_object: Box<dyn RenderObject> = match (text, spans) {
+            (Some(content), _) => Box::new(Text::with_dom(dom, content)),
+            (None, Some(spans)) => Box::new(RichText::with_dom(dom, spans)),
+            (None, None) => Box::new(Node { dom }),
+        };
         let mut render_node = TreeNode::new(render_object);
 
         // 事件节点
@@ -167,12 +520,16 @@ impl Element {
 
             render_node.append(child_render_node);
 
-            if let Some(event_node) = &mut event_node {
-                if let Some(child_event_node) = child_event_node {
-                    event_node.append(child_event_node);
-                }
-            } else {
-                event_node = child_event_node;
+            if let Some(child_event_node) = child_event_node {
+                // 没有监听器的节点 也需要占位, 否则 兄弟节点的事件节点 会丢失
+                event_node
+                    .get_or_insert_with(|| {
+                        TreeNode::new(EventObject {
+                            node: render_node.clone(),
+                            event: EventListener::default(),
+                        })
+                    })
+                    .append(child_event_node);
             }
             if let Some(data_node) = &mut data_node {
                 if let Some(child_data_node) = child_data_node {
@@ -217,6 +574,313 @@ impl RenderObject for Node {
     }
 }
 
+impl From<&str> for Element {
+    fn from(content: &str) -> Self {
+        Self::text(content)
+    }
+}
+
+impl From<String> for Element {
+    fn from(content: String) -> Self {
+        Self::text(content)
+    }
+}
+
+/// 上次排版的结果, 以及 排版时的 最大宽度, 样式 和 文本度量
+#[derive(Debug)]
+struct TextLayoutCache {
+    max_width: Option<f32>,
+    style: TextStyle,
+    measurer: (usize, usize),
+    layout: Rc<TextLayout>,
+}
+
+/// 节点样式中的 段落样式
+fn text_style(dom: &DomElement) -> TextStyle {
+    let inherited = dom.inherited();
+    TextStyle {
+        size: inherited.font_size,
+        line_height: inherited.line_height,
+        family: inherited.font_family.clone(),
+        weight: inherited.font_weight,
+        style: inherited.font_style,
+        align: dom.style().text_align,
+    }
+}
+
+/// 最大宽度, 样式 和 文本度量 都不变时 复用 上次排版的结果
+fn cached_layout(
+    cache: &RefCell<Option<TextLayoutCache>>,
+    max_width: Option<f32>,
+    style: TextStyle,
+    layout: impl FnOnce(&TextStyle) -> TextLayout,
+) -> Rc<TextLayout> {
+    let measurer = measurer_key(&current_measurer());
+    let mut cache = cache.borrow_mut();
+    match cache.as_ref() {
+        Some(cached)
+            if cached.max_width == max_width
+                && cached.style == style
+                && cached.measurer == measurer =>
+        {
+            cached.layout.clone()
+        }
+        _ => {
+            let layout = Rc::new(layout(&style));
+            *cache = Some(TextLayoutCache {
+                max_width,
+                style,
+                measurer,
+                layout: layout.clone(),
+            });
+            layout
+        }
+    }
+}
+
+/// 绘制排版结果: 先绘制 背景高亮, 再绘制 字形, 最后绘制 下划线和删除线;
+/// 片段没有设置颜色时 使用 节点的文本颜色
+fn paint_text(
+    painter: &mut Box<dyn Painter>,
+    origin: Vec2,
+    layout: &TextLayout,
+    spans: &[Span],
+    color: Color,
+) {
+    let span_color = |span: usize| spans.get(span).and_then(|s| s.color).unwrap_or(color);
+
+    let backgrounds = layout
+        .runs
+        .iter()
+        .filter_map(|run| {
+            let background = spans.get(run.span)?.background?;
+            let rect = run.rect.translate(origin).to_non_empty()?;
+            Some(Quad::new(rect.to_rect(), background))
+        })
+        .collect::<Vec<_>>();
+    if !backgrounds.is_empty() {
+        painter.rect(&backgrounds);
+    }
+
+    // 相邻的 颜色相同的片段 一起绘制
+    let mut start = 0;
+    for (i, run) in layout.runs.iter().enumerate() {
+        let color = span_color(run.span);
+        let next = layout.runs.get(i + 1).map(|next| span_color(next.span));
+        if next == Some(color) {
+            continue;
+        }
+        let glyphs = layout.runs[start..=i]
+            .iter()
+            .flat_map(|run| layout.glyphs[run.glyphs.clone()].iter())
+            .map(|glyph| {
+                let mut glyph = *glyph;
+                glyph.position += origin;
+                glyph
+            })
+            .collect::<Vec<_>>();
+        if !glyphs.is_empty() {
+            painter.text(&glyphs, color);
+        }
+        start = i + 1;
+    }
+
+    let lines = layout
+        .runs
+        .iter()
+        .flat_map(|run| {
+            let color = span_color(run.span);
+            [run.underline, run.strikethrough]
+                .into_iter()
+                .flatten()
+                .filter_map(move |line| {
+                    let rect = line.translate(origin).to_non_empty()?;
+                    Some(Quad::new(rect.to_rect(), color))
+                })
+        })
+        .collect::<Vec<_>>();
+    if !lines.is_empty() {
+        painter.rect(&lines);
+    }
+}
+
+/// 文本节点: 内容 按 可用宽度 换行, 尺寸 由排版结果决定; 只绘制字形, 不绘制背景
+#[derive(Debug)]
+pub struct Text {
+    dom: DomElement,
+    content: String,
+    cache: RefCell<Option<TextLayoutCache>>,
+}
+
+impl Text {
+    pub fn new(content: impl Into<String>) -> Self {
+        let mut dom = DomElement::new("text");
+        dom.set_layout(Layout::InlineBlock);
+        Self::with_dom(dom, content.into())
+    }
+
+    fn with_dom(dom: DomElement, content: String) -> Self {
+        Self {
+            dom,
+            content,
+            cache: RefCell::new(None),
+        }
+    }
+
+    #[inline]
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// 修改内容, 改变时 标记需要重新布局
+    pub fn set_content(&mut self, content: impl Into<String>) {
+        let content = content.into();
+        if self.content != content {
+            self.content = content;
+            self.cache.take();
+            self.dom.set_dirty(true);
+        }
+    }
+
+    /// 按 最大宽度 排版, None 表示 只在强制换行处 换行
+    pub fn text_layout(&self, max_width: Option<f32>) -> Rc<TextLayout> {
+        let style = text_style(&self.dom);
+        cached_layout(&self.cache, max_width, style, |style| {
+            layout_text(&self.content, style, max_width)
+        })
+    }
+}
+
+impl RenderObject for Text {
+    fn intrinsic_width(&self) -> IntrinsicSize {
+        let (min_content, max_content) = measure_text(&self.content, &text_style(&self.dom));
+        IntrinsicSize {
+            min_content,
+            max_content,
+        }
+    }
+
+    fn content_height(&self, width: f32) -> f32 {
+        self.text_layout(Some(width)).size.y
+    }
+
+    fn paint(&mut self, painter: &mut Box<dyn Painter>) {
+        let area = self.dom.ava_box();
+        let layout = self.text_layout(Some(area.width()));
+        paint_text(painter, area.min, &layout, &[], self.dom.inherited().color);
+    }
+
+    #[inline]
+    fn dom(&self) -> &DomElement {
+        &self.dom
+    }
+
+    #[inline]
+    fn dom_mut(&mut self) -> &mut DomElement {
+        &mut self.dom
+    }
+
+    #[inline]
+    fn node_id(&self) -> Id {
+        self.dom.node_id()
+    }
+}
+
+/// 富文本节点: 多个片段 作为一个段落 排版, 每个片段 有自己的样式;
+/// 节点的文本样式 是片段的默认样式, 节点自身 不绘制背景
+#[derive(Debug)]
+pub struct RichText {
+    dom: DomElement,
+    spans: Vec<Span>,
+    cache: RefCell<Option<TextLayoutCache>>,
+}
+
+impl RichText {
+    pub fn new(spans: Vec<Span>) -> Self {
+        let mut dom = DomElement::new("rich-text");
+        dom.set_layout(Layout::InlineBlock);
+        Self::with_dom(dom, spans)
+    }
+
+    fn with_dom(dom: DomElement, spans: Vec<Span>) -> Self {
+        Self {
+            dom,
+            spans,
+            cache: RefCell::new(None),
+        }
+    }
+
+    #[inline]
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// 修改片段, 改变时 标记需要重新布局
+    pub fn set_spans(&mut self, spans: Vec<Span>) {
+        if self.spans != spans {
+            self.spans = spans;
+            self.cache.take();
+            self.dom.set_dirty(true);
+        }
+    }
+
+    /// 在末尾 追加片段, 如: 日志 和 聊天消息
+    pub fn push_span(&mut self, span: impl Into<Span>) {
+        self.spans.push(span.into());
+        self.cache.take();
+        self.dom.set_dirty(true);
+    }
+
+    /// 按 最大宽度 排版, None 表示 只在强制换行处 换行
+    pub fn text_layout(&self, max_width: Option<f32>) -> Rc<TextLayout> {
+        let style = text_style(&self.dom);
+        cached_layout(&self.cache, max_width, style, |style| {
+            layout_spans(&self.spans, style, max_width)
+        })
+    }
+}
+
+impl RenderObject for RichText {
+    fn intrinsic_width(&self) -> IntrinsicSize {
+        let (min_content, max_content) = measure_spans(&self.spans, &text_style(&self.dom));
+        IntrinsicSize {
+            min_content,
+            max_content,
+        }
+    }
+
+    fn content_height(&self, width: f32) -> f32 {
+        self.text_layout(Some(width)).size.y
+    }
+
+    fn paint(&mut self, painter: &mut Box<dyn Painter>) {
+        let area = self.dom.ava_box();
+        let layout = self.text_layout(Some(area.width()));
+        paint_text(
+            painter,
+            area.min,
+            &layout,
+            &self.spans,
+            self.dom.inherited().color,
+        );
+    }
+
+    #[inline]
+    fn dom(&self) -> &DomElement {
+        &self.dom
+    }
+
+    #[inline]
+    fn dom_mut(&mut self) -> &mut DomElement {
+        &mut self.dom
+    }
+
+    #[inline]
+    fn node_id(&self) -> Id {
+        self.dom.node_id()
+    }
+}
+
 #[derive(Default)]
 pub struct RenderTree {
     inner: Option<TreeNode<Box<dyn RenderObject>>>,
@@ -11,6 +11,12 @@ pub enum Error {
 
     #[error("尺寸参数错误")]
     SizeFormatError,
+
+    #[error("字体加载失败: {0}")]
+    FontError(String),
+
+    #[error("样式表解析失败: {0}")]
+    StyleSheetError(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -10,11 +10,12 @@ impl Counter {
         Self(AtomicU64::new(0))
     }
     pub fn next_nonzero(&self) -> NonZeroU64 {
-        unsafe { NonZeroU64::new_unchecked(self.0.fetch_add(1, Ordering::Relaxed)) }
+        // 从1开始计数, 保证不为0
+        unsafe { NonZeroU64::new_unchecked(self.0.fetch_add(1, Ordering::Relaxed) + 1) }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Id(NonZeroU64);
 
 impl Id {
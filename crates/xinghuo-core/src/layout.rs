@@ -0,0 +1,1986 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::element::RenderObject;
+use crate::id::Id;
+use crate::prelude::*;
+use crate::text::{measurer_key, set_current_measurer, FontSystem, TextMeasurer};
+use crate::{
+    AlignItems, DomElement, FlexWrap, GridPlacement, GridTrack, InheritedStyle, JustifyContent,
+    Layout, Position, Size, Style, TextAlign, TrackSize, Units, VerticalAlign,
+};
+
+/// 布局算法: 根据父节点的 尺寸和有效区域, 计算并更新 所有子节点的 宽度/高度/Area
+pub trait LayoutAlgorithm {
+    fn layout_children(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement);
+}
+
+/// 块布局, 也用于 行内块 的子节点
+pub struct BlockLayout;
+
+impl LayoutAlgorithm for BlockLayout {
+    fn layout_children(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+        layout_block(node, dom);
+    }
+}
+
+/// 行内布局
+pub struct InlineLayout;
+
+impl LayoutAlgorithm for InlineLayout {
+    fn layout_children(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+        let children = in_flow_children(node).collect::<Vec<_>>();
+        layout_inline(&children, dom.size(), dom.ava_box(), dom.style().text_align);
+    }
+}
+
+/// 弹性布局, row 为 true 时 主轴为水平方向
+pub struct FlexLayout {
+    pub row: bool,
+}
+
+impl LayoutAlgorithm for FlexLayout {
+    fn layout_children(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+        layout_flex(node, dom, self.row);
+    }
+}
+
+/// 网格布局
+pub struct GridLayout;
+
+impl LayoutAlgorithm for GridLayout {
+    fn layout_children(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+        layout_grid(node, dom);
+    }
+}
+
+/// 层叠布局
+pub struct StackLayout;
+
+impl LayoutAlgorithm for StackLayout {
+    fn layout_children(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+        layout_stack(node, dom);
+    }
+}
+
+/// 布局引擎: 只计算 渲染树中每个节点的 ava_box, 不依赖 Painter, 可以在没有窗口的环境下使用
+pub struct LayoutEngine {
+    /// 自定义的布局算法, 优先于 内置的布局算法
+    algorithms: Vec<(Layout, Box<dyn LayoutAlgorithm>)>,
+    /// 窗口的缩放比例, 1dp = scale_factor px
+    scale_factor: f32,
+    /// 根字体大小, 1rem = root_font_size px
+    root_font_size: f32,
+    /// 上次布局 使用的单位换算, viewport 是 根节点的尺寸, 也是 固定定位的包含块
+    units: Units,
+    /// 计算文本尺寸, 默认使用 当前线程共享的字体集合
+    text_measurer: Rc<dyn TextMeasurer>,
+    /// 上次布局 使用的文本度量 和 它的版本
+    measurer: (usize, usize),
+}
+
+impl Default for LayoutEngine {
+    fn default() -> Self {
+        let units = Units::default();
+        let text_measurer: Rc<dyn TextMeasurer> = FontSystem::shared();
+        Self {
+            algorithms: Vec::new(),
+            scale_factor: units.scale_factor,
+            root_font_size: units.root_font_size,
+            units,
+            measurer: measurer_key(&text_measurer),
+            text_measurer,
+        }
+    }
+}
+
+impl LayoutEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用自定义的布局算法, 替换 layout 对应的 内置布局算法
+    pub fn with_algorithm(
+        mut self,
+        layout: Layout,
+        algorithm: impl LayoutAlgorithm + 'static,
+    ) -> Self {
+        self.algorithms.retain(|(l, _)| *l != layout);
+        self.algorithms.push((layout, Box::new(algorithm)));
+        self
+    }
+
+    /// 设置 窗口的缩放比例, 用于 dp 单位
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// 设置 根字体大小, 用于 rem 单位
+    pub fn set_root_font_size(&mut self, font_size: f32) {
+        self.root_font_size = font_size;
+    }
+
+    /// 设置 计算文本尺寸的 文本度量, 一般是 Painter 绘制时使用的字体集合
+    pub fn set_text_measurer(&mut self, text_measurer: Rc<dyn TextMeasurer>) {
+        self.text_measurer = text_measurer;
+    }
+
+    /// 计算整个渲染树的布局, viewport 是 根节点的尺寸
+    /// 增量布局: 只重新计算 样式改变 (dirty) 的节点 和 尺寸依赖它们的祖先节点 的子级,
+    /// 以及 有效区域改变的节点 的子级; 返回 样式改变 或 有效区域改变 的节点
+    pub fn compute(&mut self, root: &TreeNode<Box<dyn RenderObject>>, viewport: Vec2) -> Vec<Id> {
+        /*
+            根节点布局, 强制到 viewport 尺寸, 且 块布局
+        */
+        {
+            let mut root = root.clone();
+            let mut root_node = root.borrow_mut();
+            let dom = root_node.dom_mut();
+            let mut style = Style::default();
+            style.width = viewport.x.into();
+            style.height = viewport.y.into();
+            dom.set_style(style);
+            dom.set_layout(Layout::Block);
+            dom.set_ava_box(Box2::from_size(viewport));
+        }
+        // viewport 或 单位换算 改变时 固定定位的包含块 和 vw/vh/dp 等单位 也改变, 全部重新布局
+        let units = Units {
+            viewport,
+            scale_factor: self.scale_factor,
+            root_font_size: self.root_font_size,
+            font_size: self.root_font_size,
+        };
+        // 文本度量 或 已加载的字体 改变时 文本尺寸 也改变
+        let measurer = measurer_key(&self.text_measurer);
+        let full = units != self.units || measurer != self.measurer;
+        self.units = units;
+        self.measurer = measurer;
+        Units::set_current(units);
+        set_current_measurer(self.text_measurer.clone());
+
+        /*
+            继承的文本属性: 先序遍历, 由 父节点的计算值 和 自身样式 计算, 计算值改变的节点 标记为 dirty;
+            自身 和 父节点 都不是 dirty 时 计算值 不变, 跳过
+        */
+        let root_inherited = InheritedStyle {
+            font_size: self.root_font_size,
+            ..InheritedStyle::default()
+        };
+        for mut node in root.descendants() {
+            let parent = node.parent();
+            let parent_dirty = parent
+                .as_ref()
+                .map(|parent| parent.borrow().dom().is_dirty())
+                .unwrap_or(true);
+            if !full && !parent_dirty && !node.borrow().dom().is_dirty() {
+                continue;
+            }
+            let parent = parent
+                .map(|parent| parent.borrow().dom().inherited().clone())
+                .unwrap_or_else(|| root_inherited.clone());
+            node.borrow_mut().dom_mut().inherit(&parent);
+        }
+
+        /*
+            标记 需要重新布局子级的节点
+        */
+        let mut relayout = HashSet::new();
+        let mut dirty = HashSet::new();
+        for node in root.descendants() {
+            if !node.borrow().dom().is_dirty() {
+                continue;
+            }
+            dirty.insert(node.borrow().dom().node_id());
+            // 自身的尺寸和位置 由父节点计算, 父节点的尺寸 依赖子级时 继续向上标记
+            for ancestor in node.ancestors() {
+                relayout.insert(ancestor.borrow().dom().node_id());
+                if ancestor != node && !size_depends_on_children(&ancestor) {
+                    break;
+                }
+            }
+        }
+
+        // 先序遍历: 父节点的布局 计算完成后, 才会计算 子节点的布局
+        let mut changed = dirty.iter().copied().collect::<Vec<_>>();
+        for node in root.descendants() {
+            if !full && !relayout.contains(&node.borrow().dom().node_id()) {
+                continue;
+            }
+            let dom = node.borrow().dom().clone();
+
+            let before = node
+                .children()
+                .map(|child| child.borrow().dom().ava_box())
+                .collect::<Vec<_>>();
+            self.layout_children(&node, &dom);
+            self.layout_positioned(&node, &dom);
+            if dom.style().overflow.is_clip() {
+                scroll_children(&node, &dom);
+            }
+
+            // 有效区域改变的子级, 它的子级 也需要重新布局
+            for (child, before) in node.children().zip(before) {
+                let child_ref = child.borrow();
+                let cdom = child_ref.dom();
+                if cdom.ava_box() != before {
+                    relayout.insert(cdom.node_id());
+                    if !dirty.contains(&cdom.node_id()) {
+                        changed.push(cdom.node_id());
+                    }
+                }
+            }
+        }
+
+        for mut node in root.descendants() {
+            node.borrow_mut().dom_mut().set_dirty(false);
+        }
+        changed
+    }
+
+    /// 相对定位的子级 在正常布局后 按偏移量移动;
+    /// 绝对定位/固定定位的子级 脱离文档流, 根据包含块 计算位置和尺寸
+    fn layout_positioned(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+        for mut child in node.children() {
+            let position = child.borrow().dom().style().position;
+            match position {
+                Position::Static => {}
+                Position::Relative => {
+                    let mut child = child.borrow_mut();
+                    let cdom = child.dom_mut();
+                    let offset = cdom.with_units(|| relative_offset(cdom.style(), dom.size()));
+                    cdom.set_ava_box(cdom.ava_box().translate(offset));
+                }
+                Position::Absolute => {
+                    // 包含块: 最近的 非静态定位的祖先节点, 没有则是 viewport
+                    let containing_block = node
+                        .ancestors()
+                        .find(|n| n.borrow().dom().style().position != Position::Static)
+                        .map(|n| n.borrow().dom().ava_box())
+                        .unwrap_or_else(|| Box2::from_size(self.units.viewport));
+                    layout_absolute(&child, containing_block, dom.ava_box().min);
+                }
+                Position::Fixed => {
+                    let containing_block = Box2::from_size(self.units.viewport);
+                    layout_absolute(&child, containing_block, dom.ava_box().min);
+                }
+            }
+        }
+    }
+
+    /// 根据当前节点的布局, 计算并更新 所有子节点的布局
+    fn layout_children(&self, node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+        let layout = dom.layout();
+        if let Some((_, algorithm)) = self.algorithms.iter().find(|(l, _)| *l == layout) {
+            algorithm.layout_children(node, dom);
+            return;
+        }
+
+        match layout {
+            Layout::Inline => InlineLayout.layout_children(node, dom),
+            // 行内块 对外是行内元素, 对内和块元素一样
+            Layout::InlineBlock | Layout::Block => BlockLayout.layout_children(node, dom),
+            Layout::RowFlex => FlexLayout { row: true }.layout_children(node, dom),
+            Layout::ColFlex => FlexLayout { row: false }.layout_children(node, dom),
+            Layout::Grid => GridLayout.layout_children(node, dom),
+            Layout::Stack => StackLayout.layout_children(node, dom),
+        }
+    }
+}
+
+/// 内在宽度 (不包括边沿)
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct IntrinsicSize {
+    /// 最小内容宽度: 尽可能换行时 的宽度
+    pub min_content: f32,
+    /// 最大内容宽度: 不换行时 的宽度
+    pub max_content: f32,
+}
+
+/// 根据父节点尺寸 更新 子级的盒子轮廓
+fn update_edges(child: &mut TreeNode<Box<dyn RenderObject>>, parent_size: Vec2) {
+    child.borrow_mut().dom_mut().update_edges(parent_size);
+}
+
+/// 不依赖父节点尺寸的 宽度, 宽度为 auto 时 由 固定高度 和 宽高比 计算; 行内元素 忽略宽度
+fn fixed_width(dom: &DomElement) -> Option<f32> {
+    if dom.layout() == Layout::Inline {
+        return None;
+    }
+    dom.fixed_width().or_else(|| {
+        dom.fixed_height()
+            .and_then(|height| dom.ratio_width(dom.constrain_height(height, 0.0)))
+    })
+}
+
+/// 宽度为 auto 时, 由 根据父节点尺寸计算的高度 和 宽高比 计算宽度
+fn ratio_width(dom: &DomElement, parent_size: Vec2) -> Option<f32> {
+    let height = dom.resolve_height(parent_size.y)?;
+    dom.ratio_width(dom.constrain_height(height, parent_size.y))
+}
+
+/// 节点的 内在宽度 (不包括边沿): 由 自身内容 和 子级 共同决定
+pub fn intrinsic_width(node: &TreeNode<Box<dyn RenderObject>>) -> IntrinsicSize {
+    let node_ref = node.borrow();
+    let dom = node_ref.dom();
+    if let Some(width) = fixed_width(dom) {
+        let width = dom.constrain_width(width, 0.0);
+        return IntrinsicSize {
+            min_content: width,
+            max_content: width,
+        };
+    }
+
+    let layout = dom.layout();
+    let gap = dom.with_units(|| dom.style().gap.fixed()).unwrap_or(0.0);
+    // 网格中 每行子级的数量
+    let grid_columns = dom.style().grid_template_columns.0.len().max(1);
+
+    let mut size = node_ref.intrinsic_width();
+    // 排在同一行的 子级 宽度之和, 以及 子级数量
+    let mut line = 0.0f32;
+    let mut line_count = 0;
+    for child in in_flow_children(node) {
+        let child_size = intrinsic_width(&child);
+        let edge = child.borrow().dom().edge_width();
+        size.min_content = size.min_content.max(child_size.min_content + edge);
+        let outer = child_size.max_content + edge;
+
+        let same_line = match layout {
+            Layout::Inline | Layout::RowFlex => true,
+            Layout::Grid => line_count < grid_columns,
+            Layout::Block | Layout::InlineBlock => is_inline_level(&child),
+            Layout::ColFlex | Layout::Stack => false,
+        };
+        if !same_line {
+            size.max_content = size.max_content.max(line);
+            line = 0.0;
+            line_count = 0;
+        }
+        if same_line || layout == Layout::Grid {
+            if line_count > 0 && matches!(layout, Layout::RowFlex | Layout::Grid) {
+                line += gap;
+            }
+            line += outer;
+            line_count += 1;
+        } else {
+            size.max_content = size.max_content.max(outer);
+        }
+    }
+    size.max_content = size.max_content.max(line);
+
+    if layout != Layout::Inline {
+        size.min_content = dom.constrain_width(size.min_content, 0.0);
+        size.max_content = dom.constrain_width(size.max_content, 0.0);
+    }
+    size
+}
+
+/// 收缩到内容: 不小于 最小内容宽度, 不大于 最大内容宽度, 尽量使用 可用宽度
+pub fn shrink_to_fit(node: &TreeNode<Box<dyn RenderObject>>, available: f32) -> f32 {
+    let size = intrinsic_width(node);
+    size.min_content.max(available).min(size.max_content)
+}
+
+/// 内容宽度为 width 时, 节点的 内容高度 (不包括边沿): 由 自身内容 和 子级 共同决定
+pub fn measure_height(node: &TreeNode<Box<dyn RenderObject>>, width: f32) -> f32 {
+    let node_ref = node.borrow();
+    let dom = node_ref.dom();
+    let layout = dom.layout();
+    if layout != Layout::Inline {
+        if let Some(height) = dom.fixed_height().or_else(|| dom.ratio_height(width)) {
+            return dom.constrain_height(height, 0.0);
+        }
+    }
+
+    let gap = dom.with_units(|| dom.style().gap.fixed()).unwrap_or(0.0);
+    let grid_columns = dom.style().grid_template_columns.0.len().max(1);
+
+    // 子级的 盒子尺寸, 宽度为 auto 时 fill 则占满宽度, 否则 收缩到内容
+    let child_outer = |child: &TreeNode<Box<dyn RenderObject>>, fill: bool| {
+        let child_ref = child.borrow();
+        let cdom = child_ref.dom();
+        let edge = vec2(cdom.edge_width(), cdom.edge_height());
+        let child_width = fixed_width(cdom).unwrap_or_else(|| {
+            if fill {
+                width - edge.x
+            } else {
+                shrink_to_fit(child, width - edge.x)
+            }
+        });
+        let child_width = cdom.constrain_width(child_width.max(0.0), width);
+        vec2(child_width, measure_height(child, child_width)) + edge
+    };
+
+    let mut height = 0.0f32;
+    // 当前行的 宽度 和 高度, 以及 子级数量
+    let mut line = Vec2::ZERO;
+    let mut line_count = 0;
+    // 上一个块级子级的 下外边距, 和 layout_block 一样 合并外边距
+    let mut prev_margin_bottom: Option<f32> = None;
+    for (i, child) in in_flow_children(node).enumerate() {
+        match layout {
+            Layout::Block | Layout::InlineBlock => {
+                if is_inline_level(&child) {
+                    let outer = child_outer(&child, false);
+                    if line.x > 0.0 && line.x + outer.x > width {
+                        height += line.y;
+                        line = Vec2::ZERO;
+                    }
+                    line = vec2(line.x + outer.x, line.y.max(outer.y));
+                    prev_margin_bottom = None;
+                } else {
+                    let margin = child.borrow().dom().margin();
+                    let overlap = prev_margin_bottom
+                        .map_or(0.0, |prev| collapsed_overlap(prev, margin.top().into()));
+                    height += line.y + child_outer(&child, true).y - overlap;
+                    line = Vec2::ZERO;
+                    prev_margin_bottom = Some(margin.bottom().into());
+                }
+            }
+            Layout::Inline | Layout::RowFlex => {
+                line.y = line.y.max(child_outer(&child, false).y);
+            }
+            Layout::ColFlex => {
+                if i > 0 {
+                    height += gap;
+                }
+                height += child_outer(&child, true).y;
+            }
+            Layout::Stack => {
+                let stretch = child.borrow().dom().style().stretch;
+                height = height.max(child_outer(&child, stretch).y);
+            }
+            Layout::Grid => {
+                if line_count == grid_columns {
+                    height += line.y + gap;
+                    line = Vec2::ZERO;
+                    line_count = 0;
+                }
+                line.y = line.y.max(child_outer(&child, false).y);
+                line_count += 1;
+            }
+        }
+    }
+    height += line.y;
+
+    let height = height.max(node_ref.content_height(width));
+    if layout == Layout::Inline {
+        height
+    } else {
+        dom.constrain_height(height, 0.0)
+    }
+}
+
+/// 参与正常布局的子级, 不包括 绝对定位/固定定位 的子级
+fn in_flow_children(
+    node: &TreeNode<Box<dyn RenderObject>>,
+) -> impl Iterator<Item = TreeNode<Box<dyn RenderObject>>> {
+    node.children().filter(|child| {
+        !matches!(
+            child.borrow().dom().style().position,
+            Position::Absolute | Position::Fixed
+        )
+    })
+}
+
+/// 节点的尺寸 是否依赖子级: 宽度或高度为 auto, 或者是 行内元素
+fn size_depends_on_children(node: &TreeNode<Box<dyn RenderObject>>) -> bool {
+    let node_ref = node.borrow();
+    let dom = node_ref.dom();
+    dom.layout() == Layout::Inline || dom.style().width.is_auto() || dom.style().height.is_auto()
+}
+
+/// 裁剪子级的节点: 记录 子级内容的尺寸, 并按 滚动偏移量 移动子级 (固定定位的子级 除外);
+/// 子级的子级 在之后 根据子级的有效区域 布局, 所以 只需要移动 直接子级
+fn scroll_children(node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+    let origin = dom.ava_box().min;
+    let scroll_size = node
+        .children()
+        .filter(|child| child.borrow().dom().style().position != Position::Fixed)
+        .map(|child| child.borrow().dom().box_rect().max - origin)
+        .fold(Vec2::ZERO, Vec2::max);
+
+    let offset = {
+        let mut node = node.clone();
+        let mut node_ref = node.borrow_mut();
+        node_ref.dom_mut().set_scroll_size(scroll_size);
+        node_ref.dom().scroll_offset()
+    };
+    if offset == Vec2::ZERO {
+        return;
+    }
+
+    for mut child in node.children() {
+        let mut child = child.borrow_mut();
+        let cdom = child.dom_mut();
+        if cdom.style().position != Position::Fixed {
+            cdom.set_ava_box(cdom.ava_box().translate(-offset));
+        }
+    }
+}
+
+/// 相对定位的偏移量, left 优先于 right, top 优先于 bottom
+fn relative_offset(style: &Style, parent_size: Vec2) -> Vec2 {
+    let resolve = |size: Option<Size>, max_value: f32| size.map(|s| s.update(max_value).value());
+    let x = resolve(style.left, parent_size.x)
+        .or_else(|| resolve(style.right, parent_size.x).map(|right| -right))
+        .unwrap_or(0.0);
+    let y = resolve(style.top, parent_size.y)
+        .or_else(|| resolve(style.bottom, parent_size.y).map(|bottom| -bottom))
+        .unwrap_or(0.0);
+    vec2(x, y)
+}
+
+/// 计算 绝对定位子级 在一个轴上的 (盒子起始位置, 内容尺寸)
+/// size 为 None 时, 尺寸 由包含块 减去 start 和 end 得到;
+/// start 和 end 都没有设置时, 使用 static_pos, 即 子级在正常布局中 的位置
+fn absolute_axis(
+    start: Option<f32>,
+    end: Option<f32>,
+    size: Option<f32>,
+    edge: f32,
+    cb_min: f32,
+    cb_len: f32,
+    static_pos: f32,
+) -> (f32, f32) {
+    let size = size
+        .unwrap_or_else(|| (cb_len - start.unwrap_or(0.0) - end.unwrap_or(0.0) - edge).max(0.0));
+    let pos = match (start, end) {
+        (Some(start), _) => cb_min + start,
+        (None, Some(end)) => cb_min + cb_len - end - size - edge,
+        (None, None) => static_pos,
+    };
+    (pos, size)
+}
+
+/// 绝对定位/固定定位: 根据 包含块 和 top/right/bottom/left 计算 子级的 宽度/高度/Area
+fn layout_absolute(
+    child: &TreeNode<Box<dyn RenderObject>>,
+    containing_block: Box2,
+    static_pos: Vec2,
+) {
+    let cb_size = containing_block.size();
+    let mut child = child.clone();
+    // 更新盒子轮廓
+    update_edges(&mut child, cb_size);
+
+    let ((x, width), (y, height)) = {
+        let child_ref = child.borrow();
+        let cdom = child_ref.dom();
+        let style = cdom.style();
+        let resolve = |size: Option<Size>, max_value: f32| {
+            size.map(|s| cdom.with_units(|| s.update(max_value).value()))
+        };
+        let (left, right) = (
+            resolve(style.left, cb_size.x),
+            resolve(style.right, cb_size.x),
+        );
+        let (top, bottom) = (
+            resolve(style.top, cb_size.y),
+            resolve(style.bottom, cb_size.y),
+        );
+
+        // 宽度为 auto 时: 同时设置了 left 和 right 则拉伸, 否则 收缩到内容
+        let width = match cdom
+            .resolve_width(cb_size.x)
+            .or_else(|| ratio_width(cdom, cb_size))
+        {
+            Some(width) => Some(width),
+            None if left.is_some() && right.is_some() => None,
+            None => {
+                let available = cb_size.x - left.unwrap_or(0.0) - right.unwrap_or(0.0);
+                Some(shrink_to_fit(&child, available - cdom.edge_width()))
+            }
+        };
+        let horizontal = absolute_axis(
+            left,
+            right,
+            width.map(|w| cdom.constrain_width(w, cb_size.x)),
+            cdom.edge_width(),
+            containing_block.min.x,
+            cb_size.x,
+            static_pos.x,
+        );
+
+        // 高度为 auto 时: 有宽高比 则由宽度计算, 同时设置了 top 和 bottom 则拉伸, 否则 由子级决定
+        let height = match cdom
+            .resolve_height(cb_size.y)
+            .or_else(|| cdom.ratio_height(horizontal.1))
+        {
+            Some(height) => Some(height),
+            None if top.is_some() && bottom.is_some() => None,
+            None => Some(measure_height(&child, horizontal.1)),
+        };
+        let vertical = absolute_axis(
+            top,
+            bottom,
+            height.map(|h| cdom.constrain_height(h, cb_size.y)),
+            cdom.edge_height(),
+            containing_block.min.y,
+            cb_size.y,
+            static_pos.y,
+        );
+        (horizontal, vertical)
+    };
+
+    let mut child = child.borrow_mut();
+    let cdom = child.dom_mut();
+    cdom.set_computed_width(width);
+    cdom.set_computed_height(height);
+
+    // 更新有效区域
+    let start_point = vec2(x, y) + cdom.left_top();
+    cdom.set_ava_box(box2(start_point, start_point + vec2(width, height)));
+}
+
+/// 块布局: 块级子节点 从上到下排列, 宽度占满父节点, 较窄的子级 按 align_items/align_self 水平对齐;
+/// 连续的 行内子节点 (Inline/InlineBlock) 组成行盒, 从左到右排列;
+/// 子级的总高度 小于父节点时, 按 justify_content 整体垂直对齐
+fn layout_block(node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+    let parent_size = dom.size();
+    let style = dom.style();
+
+    // 在一个布局中, Cursor移动, 用于记录下一个 布局内节点 的起始位置, 初始位置是 父节点的area
+    let mut cursor = dom.ava_box();
+    let mut inline_run = Vec::new();
+    // 上一个块级子级的 下外边距, 中间有行盒时 为 None
+    let mut prev_margin_bottom: Option<f32> = None;
+
+    // 当前节点是Block, 则子节点的最大宽度和最大高度是确定的: 更新子节点的 宽度/高度/Area/父节点尺寸
+    for mut child in in_flow_children(node) {
+        if is_inline_level(&child) {
+            inline_run.push(child);
+            continue;
+        }
+        if !inline_run.is_empty() {
+            cursor.min.y += layout_inline(&inline_run, parent_size, cursor, style.text_align);
+            inline_run.clear();
+            prev_margin_bottom = None;
+        }
+
+        // 更新盒子轮廓
+        update_edges(&mut child, parent_size);
+
+        let (width, height, align) = {
+            let child_ref = child.borrow();
+            let cdom = child_ref.dom();
+            // 宽度为 auto 时, 当前节点的宽度 = 父节点的宽度 - 当前节点的边沿宽度
+            let width = cdom
+                .resolve_width(parent_size.x)
+                .or_else(|| ratio_width(cdom, parent_size))
+                .unwrap_or(parent_size.x - cdom.edge_width())
+                .max(0.0);
+            (
+                cdom.constrain_width(width, parent_size.x),
+                cdom.resolve_height(parent_size.y),
+                cdom.style().align_self.unwrap_or(style.align_items),
+            )
+        };
+        // 根据父节点高度 计算当前节点的高度, auto 时 由子级决定
+        let height = height.unwrap_or_else(|| measure_height(&child, width));
+
+        let mut child = child.borrow_mut();
+        let cdom = child.dom_mut();
+        let height = cdom.constrain_height(height, parent_size.y);
+        cdom.set_computed_width(width);
+        cdom.set_computed_height(height);
+
+        // 相邻块级子级的 垂直外边距 合并
+        let margin = cdom.margin();
+        if let Some(prev) = prev_margin_bottom {
+            cursor.min.y -= collapsed_overlap(prev, margin.top().into());
+        }
+        prev_margin_bottom = Some(margin.bottom().into());
+
+        // 更新有效区域: 较窄的子级 在剩余宽度中 水平对齐, 超出时 左对齐
+        let free = (parent_size.x - cdom.box_width()).max(0.0);
+        let offset = match align {
+            AlignItems::FlexStart | AlignItems::Stretch => 0.0,
+            AlignItems::Center => free / 2.0,
+            AlignItems::FlexEnd => free,
+        };
+        let start_point = cursor.min + vec2(offset, 0.0) + cdom.left_top();
+        let end_point = start_point + vec2(cdom.width(), cdom.height());
+        cdom.set_ava_box(box2(start_point, end_point));
+
+        /*
+            更新Cursor
+        */
+        // cursor向下移动
+        cursor.min.y += cdom.box_height();
+    }
+
+    if !inline_run.is_empty() {
+        cursor.min.y += layout_inline(&inline_run, parent_size, cursor, style.text_align);
+    }
+
+    // 子级 作为一个整体 垂直对齐
+    let free = parent_size.y - (cursor.min.y - dom.ava_box().min.y);
+    let offset = match style.justify_content {
+        JustifyContent::FlexStart | JustifyContent::SpaceBetween => 0.0,
+        JustifyContent::FlexEnd => free,
+        JustifyContent::Center | JustifyContent::SpaceAround | JustifyContent::SpaceEvenly => {
+            free / 2.0
+        }
+    };
+    if offset > 0.0 {
+        for mut child in in_flow_children(node) {
+            let mut child = child.borrow_mut();
+            let cdom = child.dom_mut();
+            cdom.set_ava_box(cdom.ava_box().translate(vec2(0.0, offset)));
+        }
+    }
+}
+
+/// 相邻的 下外边距 和 上外边距 合并后, 减少的距离;
+/// 合并后的外边距 = 正外边距的最大值 + 负外边距的最小值
+fn collapsed_overlap(bottom: f32, top: f32) -> f32 {
+    let collapsed = bottom.max(top).max(0.0) + bottom.min(top).min(0.0);
+    bottom + top - collapsed
+}
+
+#[inline]
+fn is_inline_level(node: &TreeNode<Box<dyn RenderObject>>) -> bool {
+    matches!(
+        node.borrow().dom().layout(),
+        Layout::Inline | Layout::InlineBlock
+    )
+}
+
+/// 层叠布局: 子级 都放置在 父节点的有效区域中, 按照锚点 对齐
+fn layout_stack(node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+    let parent_size = dom.size();
+    let content = dom.ava_box();
+
+    for mut child in in_flow_children(node) {
+        // 更新盒子轮廓
+        update_edges(&mut child, parent_size);
+
+        let (size, anchor) = {
+            let child_ref = child.borrow();
+            let cdom = child_ref.dom();
+            let cstyle = cdom.style();
+            let edge = vec2(cdom.edge_width(), cdom.edge_height());
+
+            // 宽度/高度为 auto 时: stretch 则占满, 否则 收缩到内容
+            let width = cdom
+                .resolve_width(parent_size.x)
+                .or_else(|| ratio_width(cdom, parent_size))
+                .unwrap_or_else(|| {
+                    if cstyle.stretch {
+                        parent_size.x - edge.x
+                    } else {
+                        shrink_to_fit(&child, parent_size.x - edge.x)
+                    }
+                });
+            let width = cdom.constrain_width(width.max(0.0), parent_size.x);
+            let height = cdom
+                .resolve_height(parent_size.y)
+                .or_else(|| cdom.ratio_height(width))
+                .unwrap_or_else(|| {
+                    if cstyle.stretch {
+                        parent_size.y - edge.y
+                    } else {
+                        measure_height(&child, width)
+                    }
+                });
+            let height = cdom.constrain_height(height.max(0.0), parent_size.y);
+            (
+                vec2(width, height),
+                cstyle.anchor.unwrap_or(dom.style().stack_align),
+            )
+        };
+
+        let mut child = child.borrow_mut();
+        let cdom = child.dom_mut();
+        cdom.set_computed_width(size.x);
+        cdom.set_computed_height(size.y);
+
+        // 更新有效区域: 盒子 在剩余空间中 按锚点 对齐
+        let outer = size + vec2(cdom.edge_width(), cdom.edge_height());
+        let start_point = content.min + (parent_size - outer) * anchor.factor() + cdom.left_top();
+        cdom.set_ava_box(box2(start_point, start_point + size));
+    }
+}
+
+/// 行盒中的 一个子级
+struct InlineItem {
+    node: TreeNode<Box<dyn RenderObject>>,
+    /// 内容尺寸
+    size: Vec2,
+    /// 盒子尺寸 (包括 margin/border/padding)
+    outer: Vec2,
+    vertical_align: VerticalAlign,
+}
+
+/// 行内布局: 子级 从左到右排列, 超出 父节点宽度 时换到新的行盒, 每个行盒 按 text_align 水平对齐
+/// area 是 第一个行盒的起始区域, 返回 所有行盒的总高度
+fn layout_inline(
+    children: &[TreeNode<Box<dyn RenderObject>>],
+    parent_size: Vec2,
+    area: Box2,
+    text_align: TextAlign,
+) -> f32 {
+    let max_width = area.width();
+    let mut lines: Vec<Vec<InlineItem>> = Vec::new();
+    let mut line: Vec<InlineItem> = Vec::new();
+    let mut line_width = 0.0;
+
+    for child_node in children {
+        let mut child = child_node.clone();
+        // 更新盒子轮廓
+        update_edges(&mut child, parent_size);
+
+        let item = {
+            let child_ref = child.borrow();
+            let cdom = child_ref.dom();
+            let style = cdom.style();
+            let edge = vec2(cdom.edge_width(), cdom.edge_height());
+
+            // 行内元素 无法设置宽和高, 尺寸由 排成一行的子级 决定; 行内块 使用自身的宽和高, auto 时 收缩到内容
+            let size = if cdom.layout() == Layout::Inline {
+                let width = intrinsic_width(&child).max_content;
+                vec2(width, measure_height(&child, width))
+            } else {
+                let width = cdom
+                    .resolve_width(parent_size.x)
+                    .or_else(|| ratio_width(cdom, parent_size))
+                    .unwrap_or_else(|| shrink_to_fit(&child, max_width - edge.x));
+                let width = cdom.constrain_width(width, parent_size.x);
+                let height = cdom
+                    .resolve_height(parent_size.y)
+                    .unwrap_or_else(|| measure_height(&child, width));
+                vec2(width, cdom.constrain_height(height, parent_size.y))
+            };
+
+            InlineItem {
+                node: child_node.clone(),
+                size,
+                outer: size + edge,
+                vertical_align: style.vertical_align,
+            }
+        };
+
+        if !line.is_empty() && line_width + item.outer.x > max_width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0.0;
+        }
+        line_width += item.outer.x;
+        line.push(item);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    let mut line_top = 0.0;
+    for line in lines.iter_mut() {
+        // 没有文本时, 子级的基线 是 盒子的底边, 行盒的基线 由 最高的基线对齐子级 决定
+        let baseline = line
+            .iter()
+            .filter(|i| i.vertical_align == VerticalAlign::Baseline)
+            .map(|i| i.outer.y)
+            .fold(0.0, f32::max);
+        let line_height = line.iter().map(|i| i.outer.y).fold(baseline, f32::max);
+
+        // 行盒的剩余宽度, 超出时 左对齐
+        let free = (max_width - line.iter().map(|i| i.outer.x).sum::<f32>()).max(0.0);
+        let mut x = match text_align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => free / 2.0,
+            TextAlign::Right => free,
+        };
+        for item in line.iter_mut() {
+            let top = match item.vertical_align {
+                VerticalAlign::Baseline => baseline - item.outer.y,
+                VerticalAlign::Top => 0.0,
+                VerticalAlign::Middle => (line_height - item.outer.y) / 2.0,
+                VerticalAlign::Bottom => line_height - item.outer.y,
+            };
+
+            let mut child = item.node.borrow_mut();
+            let cdom = child.dom_mut();
+            cdom.set_computed_width(item.size.x);
+            cdom.set_computed_height(item.size.y);
+
+            // 更新有效区域
+            let start_point = area.min + vec2(x, line_top + top) + cdom.left_top();
+            cdom.set_ava_box(box2(start_point, start_point + item.size));
+            drop(child);
+
+            x += item.outer.x;
+        }
+
+        line_top += line_height;
+    }
+
+    line_top
+}
+
+/// 弹性布局中的 一个子级
+struct FlexItem {
+    node: TreeNode<Box<dyn RenderObject>>,
+    /// 主轴方向的 内容尺寸
+    main: f32,
+    /// 交叉轴方向的 内容尺寸
+    cross: f32,
+    /// 交叉轴方向的尺寸 是否为 auto
+    cross_auto: bool,
+    /// 主轴方向的 边沿尺寸 (margin + border + padding)
+    main_edge: f32,
+    /// 交叉轴方向的 边沿尺寸
+    cross_edge: f32,
+    grow: f32,
+    shrink: f32,
+    align: AlignItems,
+}
+
+impl FlexItem {
+    #[inline]
+    fn outer_main(&self) -> f32 {
+        self.main + self.main_edge
+    }
+
+    #[inline]
+    fn outer_cross(&self) -> f32 {
+        self.cross + self.cross_edge
+    }
+}
+
+/// 根据主轴方向, 把 (主轴, 交叉轴) 转换为 (x, y)
+#[inline]
+fn flex_vec2(row: bool, main: f32, cross: f32) -> Vec2 {
+    if row {
+        vec2(main, cross)
+    } else {
+        vec2(cross, main)
+    }
+}
+
+/// 弹性布局: row 为 true 时 主轴为水平方向 (RowFlex), 否则为垂直方向 (ColFlex)
+/// 计算并更新 所有子节点的 宽度/高度/Area
+fn layout_flex(node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement, row: bool) {
+    let parent_size = dom.size();
+    let content = dom.ava_box();
+    let style = dom.style();
+    let (container_main, container_cross) = if row {
+        (parent_size.x, parent_size.y)
+    } else {
+        (parent_size.y, parent_size.x)
+    };
+    let gap = dom.with_units(|| style.gap.update(container_main).value());
+    let wrap = style.flex_wrap != FlexWrap::NoWrap;
+
+    /*
+        计算每个子级的 初始尺寸, 并按照 容器的主轴尺寸 分行
+    */
+    let mut lines: Vec<Vec<FlexItem>> = Vec::new();
+    let mut line: Vec<FlexItem> = Vec::new();
+    let mut line_main = 0.0;
+
+    for mut child in in_flow_children(node) {
+        // 更新盒子轮廓
+        update_edges(&mut child, parent_size);
+
+        let item = {
+            let child_ref = child.borrow();
+            let cdom = child_ref.dom();
+            let cstyle = cdom.style();
+            let sizing_edge = cdom.box_sizing_edge();
+            let (main_size, main_sizing, main_edge, cross_edge, cross) = if row {
+                (
+                    cstyle.width,
+                    sizing_edge.x,
+                    cdom.edge_width(),
+                    cdom.edge_height(),
+                    cdom.resolve_height(container_cross),
+                )
+            } else {
+                (
+                    cstyle.height,
+                    sizing_edge.y,
+                    cdom.edge_height(),
+                    cdom.edge_width(),
+                    cdom.resolve_width(container_cross),
+                )
+            };
+            let align = cstyle.align_self.unwrap_or(style.align_items);
+
+            // 初始尺寸: flex_basis > width/height > 内容尺寸
+            let basis = cdom
+                .with_units(|| {
+                    cstyle
+                        .flex_basis
+                        .filter(|basis| !basis.is_auto())
+                        .unwrap_or(main_size)
+                        .resolve(container_main)
+                })
+                .map(|basis| (basis - main_sizing).max(0.0));
+            // 有宽高比时 主轴尺寸 由确定的交叉轴尺寸 计算
+            let ratio_main = cross.and_then(|cross| {
+                if row {
+                    cdom.ratio_width(cross)
+                } else {
+                    cdom.ratio_height(cross)
+                }
+            });
+            let main = basis.or(ratio_main).unwrap_or_else(|| {
+                if row {
+                    intrinsic_width(&child).max_content
+                } else {
+                    // 列方向: 先确定宽度, 再测量高度
+                    let width = cross.unwrap_or_else(|| {
+                        if align == AlignItems::Stretch {
+                            container_cross - cross_edge
+                        } else {
+                            intrinsic_width(&child).max_content
+                        }
+                    });
+                    measure_height(&child, width)
+                }
+            });
+
+            FlexItem {
+                node: child.clone(),
+                main,
+                cross: cross.unwrap_or(0.0),
+                cross_auto: cross.is_none(),
+                main_edge,
+                cross_edge,
+                grow: cstyle.flex_grow,
+                shrink: cstyle.flex_shrink,
+                align,
+            }
+        };
+
+        let outer = item.outer_main();
+        if wrap && !line.is_empty() && line_main + gap + outer > container_main {
+            lines.push(std::mem::take(&mut line));
+            line_main = 0.0;
+        }
+        line_main += if line.is_empty() { outer } else { gap + outer };
+        line.push(item);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    let single_line = !wrap && lines.len() == 1;
+    let mut cross_cursor = 0.0;
+
+    for line in lines.iter_mut() {
+        let count = line.len() as f32;
+        let gaps = gap * (count - 1.0);
+
+        /*
+            分配剩余空间: 有剩余时 按 flex_grow 增长, 不足时 按 flex_shrink * 初始尺寸 压缩
+        */
+        let free = container_main - line.iter().map(|i| i.outer_main()).sum::<f32>() - gaps;
+        if free > 0.0 {
+            let total_grow: f32 = line.iter().map(|i| i.grow).sum();
+            if total_grow > 0.0 {
+                for item in line.iter_mut() {
+                    item.main += free * item.grow / total_grow;
+                }
+            }
+        } else if free < 0.0 {
+            let total_shrink: f32 = line.iter().map(|i| i.shrink * i.main).sum();
+            if total_shrink > 0.0 {
+                for item in line.iter_mut() {
+                    let shrink = -free * item.shrink * item.main / total_shrink;
+                    item.main = (item.main - shrink).max(0.0);
+                }
+            }
+        }
+
+        // 尺寸约束, 交叉轴为 auto 的子级 在确定主轴尺寸后 测量内容尺寸;
+        // 有宽高比时 交叉轴尺寸 由主轴尺寸计算, 不再拉伸
+        for item in line.iter_mut() {
+            let node = item.node.clone();
+            let node_ref = node.borrow();
+            let cdom = node_ref.dom();
+            if row {
+                item.main = cdom.constrain_width(item.main, container_main);
+                if item.cross_auto {
+                    item.cross = measure_height(&item.node, item.main);
+                    item.cross_auto = cdom.style().aspect_ratio.is_none();
+                }
+                item.cross = cdom.constrain_height(item.cross, container_cross);
+            } else {
+                item.main = cdom.constrain_height(item.main, container_main);
+                if item.cross_auto {
+                    item.cross = cdom
+                        .ratio_width(item.main)
+                        .unwrap_or_else(|| intrinsic_width(&item.node).max_content);
+                    item.cross_auto = cdom.style().aspect_ratio.is_none();
+                }
+                item.cross = cdom.constrain_width(item.cross, container_cross);
+            }
+        }
+
+        // 当前行 交叉轴方向的尺寸, 单行时 占满整个容器
+        let line_cross = if single_line {
+            container_cross
+        } else {
+            line.iter().map(|i| i.outer_cross()).fold(0.0, f32::max)
+        };
+
+        /*
+            主轴方向的 对齐: 起始偏移 和 子级之间的额外间隔
+        */
+        let free = container_main - line.iter().map(|i| i.outer_main()).sum::<f32>() - gaps;
+        let (mut main_cursor, spacing) = match style.justify_content {
+            JustifyContent::FlexStart => (0.0, 0.0),
+            JustifyContent::FlexEnd => (free, 0.0),
+            JustifyContent::Center => (free / 2.0, 0.0),
+            JustifyContent::SpaceBetween if count > 1.0 => (0.0, free.max(0.0) / (count - 1.0)),
+            JustifyContent::SpaceBetween => (0.0, 0.0),
+            JustifyContent::SpaceAround => {
+                let space = free.max(0.0) / count;
+                (space / 2.0, space)
+            }
+            JustifyContent::SpaceEvenly => {
+                let space = free.max(0.0) / (count + 1.0);
+                (space, space)
+            }
+        };
+
+        // 行在交叉轴上的起始位置
+        let line_start = if style.flex_wrap == FlexWrap::WrapReverse {
+            container_cross - cross_cursor - line_cross
+        } else {
+            cross_cursor
+        };
+
+        for item in line.iter_mut() {
+            /*
+                交叉轴方向的 对齐
+            */
+            let cross_offset = match item.align {
+                AlignItems::FlexStart => 0.0,
+                AlignItems::FlexEnd => line_cross - item.outer_cross(),
+                AlignItems::Center => (line_cross - item.outer_cross()) / 2.0,
+                AlignItems::Stretch => {
+                    if item.cross_auto {
+                        let node = item.node.clone();
+                        let node_ref = node.borrow();
+                        let stretch = (line_cross - item.cross_edge).max(0.0);
+                        item.cross = if row {
+                            node_ref.dom().constrain_height(stretch, container_cross)
+                        } else {
+                            node_ref.dom().constrain_width(stretch, container_cross)
+                        };
+                    }
+                    0.0
+                }
+            };
+
+            let size = flex_vec2(row, item.main, item.cross);
+            let mut child = item.node.borrow_mut();
+            let cdom = child.dom_mut();
+            cdom.set_computed_width(size.x);
+            cdom.set_computed_height(size.y);
+
+            // 更新有效区域
+            let start_point = content.min
+                + flex_vec2(row, main_cursor, line_start + cross_offset)
+                + cdom.left_top();
+            cdom.set_ava_box(box2(start_point, start_point + size));
+            drop(child);
+
+            main_cursor += item.outer_main() + gap + spacing;
+        }
+
+        cross_cursor += line_cross + gap;
+    }
+}
+
+/// 网格中的 一个子级
+struct GridItem {
+    node: TreeNode<Box<dyn RenderObject>>,
+    column: GridPlacement,
+    row: GridPlacement,
+    /// 设置的内容尺寸, auto 时 拉伸到 整个网格区域
+    width: Option<f32>,
+    height: Option<f32>,
+    /// 边沿尺寸 (margin + border + padding)
+    edge: Vec2,
+}
+
+/// 自动放置时 使用的 网格占用表, 行数 按需增长
+struct GridCells {
+    columns: usize,
+    cells: Vec<Vec<bool>>,
+}
+
+impl GridCells {
+    fn fits(&self, row: usize, column: usize, row_span: usize, column_span: usize) -> bool {
+        if column + column_span > self.columns {
+            return false;
+        }
+        (row..row + row_span).all(|r| {
+            !self
+                .cells
+                .get(r)
+                .is_some_and(|cells| cells[column..column + column_span].contains(&true))
+        })
+    }
+
+    fn occupy(&mut self, row: usize, column: usize, row_span: usize, column_span: usize) {
+        while self.cells.len() < row + row_span {
+            self.cells.push(vec![false; self.columns]);
+        }
+        for cells in &mut self.cells[row..row + row_span] {
+            for cell in &mut cells[column..(column + column_span).min(self.columns)] {
+                *cell = true;
+            }
+        }
+    }
+}
+
+/// 网格布局: 先放置 子级 (显式位置优先, 其余按行 自动放置), 再计算 轨道尺寸,
+/// 最后 子级 拉伸到 所在的网格区域 (设置了宽度/高度的子级 保持自身尺寸)
+fn layout_grid(node: &TreeNode<Box<dyn RenderObject>>, dom: &DomElement) {
+    let parent_size = dom.size();
+    let content = dom.ava_box();
+    let style = dom.style();
+    let column_gap = dom.with_units(|| style.gap.update(parent_size.x).value());
+    let row_gap = dom.with_units(|| style.gap.update(parent_size.y).value());
+
+    let mut items = Vec::new();
+    for mut child in in_flow_children(node) {
+        // 更新盒子轮廓
+        update_edges(&mut child, parent_size);
+
+        let child_ref = child.borrow();
+        let cdom = child_ref.dom();
+        let cstyle = cdom.style();
+        items.push(GridItem {
+            node: child.clone(),
+            column: cstyle.grid_column,
+            row: cstyle.grid_row,
+            width: cdom
+                .resolve_width(parent_size.x)
+                .or_else(|| ratio_width(cdom, parent_size)),
+            height: cdom.resolve_height(parent_size.y),
+            edge: vec2(cdom.edge_width(), cdom.edge_height()),
+        });
+    }
+
+    /*
+        放置子级: 列数 由 列轨道 和 显式位置 决定, 行数 按需增长
+    */
+    let columns = items
+        .iter()
+        .map(|i| i.column.start.map_or(0, |s| s - 1) + i.column.span)
+        .fold(style.grid_template_columns.0.len().max(1), usize::max);
+    let mut cells = GridCells {
+        columns,
+        cells: Vec::new(),
+    };
+    // 每个子级的 (行, 列), 从0开始
+    let mut areas = vec![(0, 0); items.len()];
+
+    // 行和列 都是显式位置的子级
+    for (item, area) in items.iter().zip(areas.iter_mut()) {
+        if let (Some(row), Some(column)) = (item.row.start, item.column.start) {
+            *area = (row - 1, column - 1);
+            cells.occupy(row - 1, column - 1, item.row.span, item.column.span);
+        }
+    }
+
+    // 其余子级 按文档顺序 自动放置
+    let (mut cursor_row, mut cursor_column) = (0, 0);
+    for (item, area) in items.iter().zip(areas.iter_mut()) {
+        let (row_span, column_span) = (item.row.span, item.column.span.min(columns));
+        *area = match (item.row.start, item.column.start) {
+            (Some(_), Some(_)) => continue,
+            // 固定行, 在该行中 查找空闲的列
+            (Some(row), None) => {
+                let row = row - 1;
+                let column = (0..=columns - column_span)
+                    .find(|c| cells.fits(row, *c, row_span, column_span))
+                    .unwrap_or(0);
+                (row, column)
+            }
+            // 固定列, 从 当前行 开始 向下查找
+            (None, Some(column)) => {
+                let column = column - 1;
+                let row = (cursor_row..)
+                    .find(|r| cells.fits(*r, column, row_span, column_span))
+                    .unwrap();
+                cursor_row = row;
+                cursor_column = column + column_span;
+                (row, column)
+            }
+            (None, None) => loop {
+                if cursor_column + column_span > columns {
+                    cursor_row += 1;
+                    cursor_column = 0;
+                    continue;
+                }
+                if cells.fits(cursor_row, cursor_column, row_span, column_span) {
+                    let area = (cursor_row, cursor_column);
+                    cursor_column += column_span;
+                    break area;
+                }
+                cursor_column += 1;
+            },
+        };
+        cells.occupy(area.0, area.1, row_span, column_span);
+    }
+
+    /*
+        计算轨道尺寸: 轨道数量不足时 补充 auto 轨道
+    */
+    let mut column_tracks = style.grid_template_columns.0.clone();
+    column_tracks.resize(columns, GridTrack::default());
+    let rows = items
+        .iter()
+        .zip(areas.iter())
+        .map(|(i, a)| a.0 + i.row.span)
+        .fold(style.grid_template_rows.0.len(), usize::max);
+    let mut row_tracks = style.grid_template_rows.0.clone();
+    row_tracks.resize(rows, GridTrack::default());
+
+    let span_size = |sizes: &[f32], start: usize, span: usize, gap: f32| {
+        sizes[start..start + span].iter().sum::<f32>() + gap * (span - 1) as f32
+    };
+
+    // 先计算列, 再根据 子级所在区域的宽度 计算行
+    let column_contributions = items
+        .iter()
+        .zip(areas.iter())
+        .map(|(i, a)| {
+            let width = i
+                .width
+                .unwrap_or_else(|| intrinsic_width(&i.node).max_content);
+            (a.1, i.column.span.min(columns), width + i.edge.x)
+        })
+        .collect::<Vec<_>>();
+    // 轨道尺寸的 em 相对于 容器的字体大小
+    let column_sizes = dom.with_units(|| {
+        size_grid_tracks(
+            &column_tracks,
+            &column_contributions,
+            parent_size.x,
+            column_gap,
+        )
+    });
+    let row_contributions = items
+        .iter()
+        .zip(areas.iter())
+        .map(|(i, a)| {
+            let height = i.height.unwrap_or_else(|| {
+                let column_span = i.column.span.min(columns);
+                let area_width = span_size(&column_sizes, a.1, column_span, column_gap);
+                measure_height(&i.node, i.width.unwrap_or(area_width - i.edge.x))
+            });
+            (a.0, i.row.span, height + i.edge.y)
+        })
+        .collect::<Vec<_>>();
+    let row_sizes = dom
+        .with_units(|| size_grid_tracks(&row_tracks, &row_contributions, parent_size.y, row_gap));
+
+    // 每条轨道的 起始位置
+    let offsets = |sizes: &[f32], gap: f32| {
+        sizes
+            .iter()
+            .scan(0.0, |offset, size| {
+                let start = *offset;
+                *offset += size + gap;
+                Some(start)
+            })
+            .collect::<Vec<_>>()
+    };
+    let column_offsets = offsets(&column_sizes, column_gap);
+    let row_offsets = offsets(&row_sizes, row_gap);
+
+    /*
+        把子级 放到 网格区域中
+    */
+    for (item, (row, column)) in items.iter_mut().zip(areas) {
+        let column_span = item.column.span.min(columns);
+        let area_size = vec2(
+            span_size(&column_sizes, column, column_span, column_gap),
+            span_size(&row_sizes, row, item.row.span, row_gap),
+        );
+        let stretch = (area_size - item.edge).max(Vec2::ZERO);
+
+        let mut child = item.node.borrow_mut();
+        let cdom = child.dom_mut();
+        let width = cdom.constrain_width(item.width.unwrap_or(stretch.x), parent_size.x);
+        let height = item
+            .height
+            .or_else(|| cdom.ratio_height(width))
+            .unwrap_or(stretch.y);
+        let size = vec2(width, cdom.constrain_height(height, parent_size.y));
+        cdom.set_computed_width(size.x);
+        cdom.set_computed_height(size.y);
+
+        // 更新有效区域
+        let start_point =
+            content.min + vec2(column_offsets[column], row_offsets[row]) + cdom.left_top();
+        cdom.set_ava_box(box2(start_point, start_point + size));
+    }
+}
+
+/// 计算 一个方向上 所有轨道的尺寸
+/// contributions 是 子级的 (起始轨道, 跨越的轨道数, 盒子尺寸), available 为0时 fr 轨道 和 auto 轨道 一样
+fn size_grid_tracks(
+    tracks: &[GridTrack],
+    contributions: &[(usize, usize, f32)],
+    available: f32,
+    gap: f32,
+) -> Vec<f32> {
+    let resolve = |size: Size| size.update(available).value();
+
+    // 只跨越一条轨道的子级, 决定 auto 轨道的内容尺寸
+    let mut content = vec![0.0f32; tracks.len()];
+    for &(start, span, size) in contributions {
+        if span == 1 {
+            content[start] = content[start].max(size);
+        }
+    }
+
+    let mut base = tracks
+        .iter()
+        .zip(content.iter())
+        .map(|(track, content)| match track.min {
+            TrackSize::Fixed(size) => resolve(size),
+            TrackSize::Fr(_) | TrackSize::Auto => *content,
+        })
+        .collect::<Vec<_>>();
+
+    // 跨越多条轨道的子级, 不足的部分 平均分配给 其中的 auto 轨道
+    for &(start, span, size) in contributions {
+        if span > 1 {
+            let spanned = start..start + span;
+            let current = base[spanned.clone()].iter().sum::<f32>() + gap * (span - 1) as f32;
+            let autos = spanned
+                .clone()
+                .filter(|i| tracks[*i].min == TrackSize::Auto)
+                .count();
+            if size > current && autos > 0 {
+                for i in spanned.filter(|i| tracks[*i].min == TrackSize::Auto) {
+                    base[i] += (size - current) / autos as f32;
+                }
+            }
+        }
+    }
+
+    let limit = tracks
+        .iter()
+        .zip(base.iter())
+        .map(|(track, base)| match track.max {
+            TrackSize::Fixed(size) => resolve(size).max(*base),
+            TrackSize::Fr(_) | TrackSize::Auto => *base,
+        })
+        .collect::<Vec<_>>();
+
+    let gaps = gap * tracks.len().saturating_sub(1) as f32;
+    let mut free = (available - base.iter().sum::<f32>() - gaps).max(0.0);
+
+    // 非 fr 轨道 增长到 上限
+    let room = base
+        .iter()
+        .zip(limit.iter())
+        .map(|(base, limit)| limit - base)
+        .sum::<f32>();
+    if room > 0.0 && free > 0.0 {
+        let ratio = (free / room).min(1.0);
+        for (base, limit) in base.iter_mut().zip(limit.iter()) {
+            *base += (limit - *base) * ratio;
+        }
+        free -= room * ratio;
+    }
+
+    // fr 轨道 按比例 分配剩余空间; 没有 fr 轨道时, auto 轨道 平分剩余空间
+    let total_fr = tracks
+        .iter()
+        .map(|track| match track.max {
+            TrackSize::Fr(fr) => fr,
+            _ => 0.0,
+        })
+        .sum::<f32>();
+    if free > 0.0 && total_fr > 0.0 {
+        let fr_base = tracks
+            .iter()
+            .zip(base.iter())
+            .filter(|(track, _)| matches!(track.max, TrackSize::Fr(_)))
+            .map(|(_, base)| base)
+            .sum::<f32>();
+        let unit = (free + fr_base) / total_fr;
+        for (track, base) in tracks.iter().zip(base.iter_mut()) {
+            if let TrackSize::Fr(fr) = track.max {
+                *base = base.max(fr * unit);
+            }
+        }
+    } else if free > 0.0 {
+        let autos = tracks.iter().filter(|t| t.max == TrackSize::Auto).count();
+        for (track, base) in tracks.iter().zip(base.iter_mut()) {
+            if track.max == TrackSize::Auto {
+                *base += free / autos as f32;
+            }
+        }
+    }
+
+    base
+}
+
+#[cfg(test)]
+pub(crate) fn compute_layout(element: crate::element::Element, viewport: Vec2) -> Vec<Box2> {
+    let (render_tree, _, _) = element.build();
+    LayoutEngine::new().compute(&render_tree, viewport);
+    render_tree
+        .descendants()
+        .map(|node| node.borrow().dom().ava_box())
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) fn assert_box(b: Box2, min: (f32, f32), max: (f32, f32)) {
+    assert_eq!(
+        (b.min.x, b.min.y, b.max.x, b.max.y),
+        (min.0, min.1, max.0, max.1)
+    );
+}
+
+#[test]
+fn test_block_layout() {
+    use crate::element::Element;
+
+    let boxes = compute_layout(
+        Element::new("div").children(vec![
+            Element::new("header").height("30"),
+            Element::new("body").height("50%"),
+            Element::new("footer").height("30"),
+        ]),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[0], (0.0, 0.0), (400.0, 300.0));
+    assert_box(boxes[1], (0.0, 0.0), (400.0, 30.0));
+    assert_box(boxes[2], (0.0, 30.0), (400.0, 180.0));
+    assert_box(boxes[3], (0.0, 180.0), (400.0, 210.0));
+}
+
+#[test]
+fn test_flex_layout() {
+    use crate::element::Element;
+
+    let boxes = compute_layout(
+        Element::new("div").child(
+            Element::new("row")
+                .layout(Layout::RowFlex)
+                .height("100")
+                .gap(10.0)
+                .align_items(AlignItems::Center)
+                .children(vec![
+                    Element::new("a").width("100").height("20"),
+                    Element::new("b").flex_grow(1.0).height("40"),
+                ]),
+        ),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[2], (0.0, 40.0), (100.0, 60.0));
+    assert_box(boxes[3], (110.0, 30.0), (400.0, 70.0));
+}
+
+#[test]
+fn test_inline_layout() {
+    use crate::element::Element;
+
+    let inline_block = || {
+        Element::new("span")
+            .layout(Layout::InlineBlock)
+            .width("150")
+            .height("20")
+    };
+    let boxes = compute_layout(
+        Element::new("div").children(vec![inline_block(), inline_block(), inline_block()]),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[1], (0.0, 0.0), (150.0, 20.0));
+    assert_box(boxes[2], (150.0, 0.0), (300.0, 20.0));
+    // 超出父节点宽度, 换到新的行盒
+    assert_box(boxes[3], (0.0, 20.0), (150.0, 40.0));
+}
+
+#[test]
+fn test_alignment() {
+    use crate::element::Element;
+    use crate::TextAlign;
+
+    let inline_block = || {
+        Element::new("span")
+            .layout(Layout::InlineBlock)
+            .width("150")
+            .height("20")
+    };
+    let boxes = compute_layout(
+        Element::new("div").child(
+            Element::new("page")
+                .height("300")
+                .justify_content(JustifyContent::Center)
+                .align_items(AlignItems::Center)
+                .text_align(TextAlign::Center)
+                .children(vec![
+                    Element::new("dialog").width("200").height("100"),
+                    Element::new("footer")
+                        .width("100")
+                        .height("40")
+                        .align_self(AlignItems::FlexEnd),
+                    inline_block(),
+                    inline_block(),
+                ]),
+        ),
+        vec2(400.0, 300.0),
+    );
+    // 子级总高度 160, 整体 垂直居中
+    assert_box(boxes[2], (100.0, 70.0), (300.0, 170.0));
+    assert_box(boxes[3], (300.0, 170.0), (400.0, 210.0));
+    // 行盒 水平居中
+    assert_box(boxes[4], (50.0, 210.0), (200.0, 230.0));
+    assert_box(boxes[5], (200.0, 210.0), (350.0, 230.0));
+}
+
+#[test]
+fn test_positioned_layout() {
+    use crate::element::Element;
+
+    let boxes = compute_layout(
+        Element::new("div").child(
+            Element::new("card")
+                .position(Position::Relative)
+                .top("10")
+                .height("100")
+                .children(vec![
+                    Element::new("badge")
+                        .position(Position::Absolute)
+                        .top("5")
+                        .right("5")
+                        .width("20")
+                        .height("20"),
+                    Element::new("overlay")
+                        .position(Position::Absolute)
+                        .left("0")
+                        .right("50%")
+                        .top("0")
+                        .bottom("0"),
+                    Element::new("content").height("40"),
+                ]),
+        ),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[1], (0.0, 10.0), (400.0, 110.0));
+    assert_box(boxes[2], (375.0, 15.0), (395.0, 35.0));
+    assert_box(boxes[3], (0.0, 10.0), (200.0, 110.0));
+    // 绝对定位的子级 脱离文档流, 不影响 其他子级的位置
+    assert_box(boxes[4], (0.0, 10.0), (400.0, 50.0));
+}
+
+#[test]
+fn test_grid_layout() {
+    use crate::element::Element;
+
+    let tile = || Element::new("tile");
+    let boxes = compute_layout(
+        Element::new("div").child(
+            Element::new("grid")
+                .layout(Layout::Grid)
+                .height("220")
+                .gap(10.0)
+                .grid_template_columns("100 1fr 1fr")
+                .grid_template_rows("repeat(2, 1fr)")
+                .children(vec![
+                    tile().grid_column("2 / span 2"),
+                    tile().grid_row(2).grid_column(1),
+                    tile(),
+                    tile().width("30").height("30"),
+                ]),
+        ),
+        vec2(400.0, 300.0),
+    );
+    // 列: 100 + 140 + 140 + 2 * 10 = 400, 行: 105 + 105 + 10 = 220
+    assert_box(boxes[2], (110.0, 0.0), (400.0, 105.0));
+    assert_box(boxes[3], (0.0, 115.0), (100.0, 220.0));
+    // 自动放置: 从 上一个子级之后 开始查找 空闲的网格
+    assert_box(boxes[4], (110.0, 115.0), (250.0, 220.0));
+    assert_box(boxes[5], (260.0, 115.0), (290.0, 145.0));
+}
+
+#[test]
+fn test_intrinsic_sizing() {
+    use crate::element::Element;
+
+    let boxes = compute_layout(
+        Element::new("div").children(vec![
+            Element::new("card").max_width("300").children(vec![
+                Element::new("title").height("40"),
+                Element::new("body").height("30"),
+            ]),
+            Element::new("empty").min_height("100").max_height("50"),
+            Element::new("chip")
+                .layout(Layout::InlineBlock)
+                .child(Element::new("icon").width("50").height("20")),
+        ]),
+        vec2(400.0, 300.0),
+    );
+    // 高度为 auto 时 由子级决定, 宽度 不超过 max_width
+    assert_box(boxes[1], (0.0, 0.0), (300.0, 70.0));
+    // min 优先于 max
+    assert_box(boxes[4], (0.0, 70.0), (400.0, 170.0));
+    // 行内块 宽度为 auto 时 收缩到内容
+    assert_box(boxes[5], (0.0, 170.0), (50.0, 190.0));
+}
+
+#[test]
+fn test_calc_size() {
+    use crate::element::Element;
+    use crate::Quat;
+
+    let boxes = compute_layout(
+        Element::new("div").children(vec![
+            Element::new("header").height("30"),
+            Element::new("body")
+                .width("calc((100% - 20) / 2 + 10)")
+                .height("100% - 60"),
+            Element::new("footer").height("30"),
+        ]),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[2], (0.0, 30.0), (200.0, 270.0));
+    assert_box(boxes[3], (0.0, 270.0), (400.0, 300.0));
+
+    // Quat 的各项 按括号外的空白 分割, calc 表达式中 可以有空白
+    let padding = Quat::from("calc(50% - 10) 5");
+    assert_eq!(padding.top(), Size::from("calc(50% - 10)"));
+    assert_eq!(padding.right(), Size::from(5.0));
+    assert_eq!(padding.bottom(), Size::from("calc(50% - 10)"));
+    let boxes = compute_layout(
+        Element::new("div").child(Element::new("card").padding("calc(10% - 10) 5")),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[1], (5.0, 30.0), (395.0, 30.0));
+}
+
+#[test]
+fn test_units() {
+    use crate::element::Element;
+
+    let (render_tree, _, _) = Element::new("div")
+        .children(vec![
+            Element::new("a").width("10vw").height("2rem"),
+            Element::new("b").width("50% - 1rem").height("10dp"),
+            Element::new("c").height("5vh"),
+            // em 相对于 自身继承的字体大小, 嵌套时 使用 最近的 font_size
+            Element::new("d").font_size(20.0).children(vec![
+                Element::new("e").width("1em + 1rem").height("0.5em"),
+                Element::new("f").font_size(10.0).width("3em").height("1em"),
+            ]),
+        ])
+        .build();
+    let boxes = |engine: &mut LayoutEngine| {
+        engine.compute(&render_tree, vec2(400.0, 300.0));
+        render_tree
+            .descendants()
+            .map(|node| node.borrow().dom().ava_box())
+            .collect::<Vec<_>>()
+    };
+
+    let mut engine = LayoutEngine::new();
+    engine.set_scale_factor(2.0);
+    let b = boxes(&mut engine);
+    assert_box(b[1], (0.0, 0.0), (40.0, 32.0));
+    assert_box(b[2], (0.0, 32.0), (184.0, 52.0));
+    assert_box(b[3], (0.0, 52.0), (400.0, 67.0));
+    assert_box(b[5], (0.0, 67.0), (36.0, 77.0));
+    assert_box(b[6], (0.0, 77.0), (30.0, 87.0));
+
+    // 缩放比例改变, dp 单位 重新计算
+    engine.set_scale_factor(1.0);
+    let b = boxes(&mut engine);
+    assert_box(b[2], (0.0, 32.0), (184.0, 42.0));
+    assert_box(b[3], (0.0, 42.0), (400.0, 57.0));
+}
+
+#[test]
+fn test_em_units() {
+    use crate::element::Element;
+
+    // em 相对于 继承的字体大小, rem 相对于 根字体大小
+    let boxes = compute_layout(
+        Element::new("div").child(Element::new("s").font_size(32.0).children(vec![
+            Element::new("x").width("1em").height("1rem"),
+            Element::new("y").width("1rem").height("1em"),
+        ])),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[2], (0.0, 0.0), (32.0, 16.0));
+    assert_box(boxes[3], (0.0, 16.0), (16.0, 48.0));
+}
+
+#[test]
+fn test_aspect_ratio() {
+    use crate::element::Element;
+
+    let (render_tree, _, _) = Element::new("div")
+        .children(vec![
+            Element::new("thumbnail").width("50%").aspect_ratio(2.0),
+            Element::new("video").height("90").aspect_ratio(2.0),
+            Element::new("row").layout(Layout::RowFlex).child(
+                Element::new("avatar")
+                    .height("50")
+                    .aspect_ratio(1.0)
+                    .padding(5.0)
+                    .box_sizing(crate::BoxSizing::BorderBox),
+            ),
+        ])
+        .build();
+    let mut engine = LayoutEngine::new();
+    let mut boxes = |viewport: Vec2| {
+        engine.compute(&render_tree, viewport);
+        render_tree
+            .descendants()
+            .map(|node| node.borrow().dom().ava_box())
+            .collect::<Vec<_>>()
+    };
+
+    let b = boxes(vec2(400.0, 300.0));
+    assert_box(b[1], (0.0, 0.0), (200.0, 100.0));
+    // 高度确定时, 宽度 由宽高比计算, 不再占满父节点
+    assert_box(b[2], (0.0, 100.0), (180.0, 190.0));
+    // 宽高比 按 border-box 计算
+    assert_box(b[4], (5.0, 195.0), (45.0, 235.0));
+
+    // 窗口尺寸改变, 保持宽高比
+    let b = boxes(vec2(800.0, 300.0));
+    assert_box(b[1], (0.0, 0.0), (400.0, 200.0));
+    assert_box(b[2], (0.0, 200.0), (180.0, 290.0));
+}
+
+#[test]
+fn test_scroll_overflow() {
+    use crate::element::Element;
+    use crate::Overflow;
+
+    let (render_tree, _, _) = Element::new("div")
+        .child(
+            Element::new("list")
+                .height("100")
+                .overflow(Overflow::Scroll)
+                .children(vec![
+                    Element::new("item").height("80"),
+                    Element::new("item").height("80"),
+                ]),
+        )
+        .build();
+    let mut engine = LayoutEngine::new();
+    engine.compute(&render_tree, vec2(400.0, 300.0));
+
+    let mut list = render_tree.first_child().unwrap();
+    assert_eq!(list.borrow().dom().scroll_size(), vec2(400.0, 160.0));
+    // 滚动偏移量 不超过 超出的部分
+    assert!(list.borrow_mut().dom_mut().scroll_by(vec2(0.0, 100.0)));
+    assert_eq!(list.borrow().dom().scroll_offset(), vec2(0.0, 60.0));
+
+    engine.compute(&render_tree, vec2(400.0, 300.0));
+    let boxes = render_tree
+        .descendants()
+        .map(|n| n.borrow().dom().ava_box())
+        .collect::<Vec<_>>();
+    assert_box(boxes[1], (0.0, 0.0), (400.0, 100.0));
+    assert_box(boxes[2], (0.0, -60.0), (400.0, 20.0));
+    assert_box(boxes[3], (0.0, 20.0), (400.0, 100.0));
+}
+
+#[test]
+fn test_box_sizing_and_margin_collapse() {
+    use crate::element::Element;
+    use crate::BoxSizing;
+
+    let boxes = compute_layout(
+        Element::new("div").children(vec![
+            Element::new("card")
+                .box_sizing(BoxSizing::BorderBox)
+                .width("200")
+                .height("100")
+                .padding(10.0)
+                .border_width(5.0)
+                .margin("0 0 20 0"),
+            Element::new("next").height("30").margin("10 0 0 0"),
+            Element::new("last").height("30").margin("-5 0 0 0"),
+        ]),
+        vec2(400.0, 300.0),
+    );
+    // BorderBox: 内容尺寸 = 200 - 2 * (10 + 5)
+    assert_box(boxes[1], (15.0, 15.0), (185.0, 85.0));
+    // 外边距 20 和 10 合并为 20
+    assert_box(boxes[2], (0.0, 120.0), (400.0, 150.0));
+    // 负外边距 直接相加
+    assert_box(boxes[3], (0.0, 145.0), (400.0, 175.0));
+}
+
+#[test]
+fn test_incremental_layout() {
+    use crate::element::Element;
+
+    let (render_tree, _, _) = Element::new("div")
+        .children(vec![
+            Element::new("header").height("30"),
+            Element::new("body")
+                .height("100")
+                .child(Element::new("row").height("20")),
+            Element::new("footer").height("30"),
+        ])
+        .build();
+    let mut engine = LayoutEngine::new();
+    engine.compute(&render_tree, vec2(400.0, 300.0));
+    // 没有改变时 不重新布局
+    assert!(engine.compute(&render_tree, vec2(400.0, 300.0)).is_empty());
+
+    let nodes = render_tree.descendants().collect::<Vec<_>>();
+    let id = |i: usize| nodes[i].borrow().dom().node_id();
+    // 设置相同的值 不标记为 dirty
+    let mut header = nodes[1].clone();
+    header.borrow_mut().dom_mut().set_height("30");
+    assert!(!header.borrow().dom().is_dirty());
+
+    // header 变高: header 和 之后的兄弟节点 改变, body 的子级 跟随移动
+    header.borrow_mut().dom_mut().set_height("50");
+    let mut changed = engine.compute(&render_tree, vec2(400.0, 300.0));
+    changed.sort_by_key(|id| nodes.iter().position(|n| n.borrow().dom().node_id() == *id));
+    assert_eq!(changed, vec![id(1), id(2), id(3), id(4)]);
+    assert_box(
+        nodes[3].borrow().dom().ava_box(),
+        (0.0, 50.0),
+        (400.0, 70.0),
+    );
+}
+
+#[test]
+fn test_stack_layout() {
+    use crate::element::Element;
+    use crate::Anchor;
+
+    let boxes = compute_layout(
+        Element::new("div").child(
+            Element::new("avatar")
+                .layout(Layout::Stack)
+                .width("100")
+                .height("100")
+                .stack_align(Anchor::Center)
+                .children(vec![
+                    Element::new("image").stretch(true),
+                    Element::new("caption").height("20").width("60"),
+                    Element::new("badge")
+                        .anchor(Anchor::TopRight)
+                        .width("10")
+                        .height("10"),
+                ]),
+        ),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[2], (0.0, 0.0), (100.0, 100.0));
+    assert_box(boxes[3], (20.0, 40.0), (80.0, 60.0));
+    assert_box(boxes[4], (90.0, 0.0), (100.0, 10.0));
+}
@@ -2,26 +2,39 @@
 #![feature(float_minimum_maximum)]
 // #[macro_use]
 // pub mod elements;
+pub mod animation;
 pub mod app;
 pub mod context;
 pub mod error;
 pub mod event;
 mod layer;
+pub mod layout;
 // pub mod macros;
 pub mod element;
 pub mod id;
 mod memory;
 pub mod node;
 pub mod painter;
+pub mod stylesheet;
+pub mod text;
+pub mod theme;
+pub mod transition;
 
+use animation::{AnimationHandle, Animations};
 use geom::color::Color;
 use geom::glam::Vec2;
 use id::Id;
 use rctree::Node;
+use std::cell::Cell;
 use std::fmt::Debug;
 use std::ops::Add;
 use std::ops::AddAssign;
+use std::ops::Mul;
 use std::ops::Sub;
+use std::time::Duration;
+use stylesheet::{Declaration, Property};
+use text::{FontStyle, FontWeight};
+use transition::{Transition, TransitionState};
 
 pub mod prelude {
     pub use crate::event::*;
@@ -55,18 +68,22 @@ pub trait NodeBuilder {
 }
 
 ///
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Layout {
     /// 无法设置宽和高
     Inline,
     /// 可以设置宽和高,
     InlineBlock,
-    /// 可以设置高度, 但宽为 父节点宽度的 100%
+    /// 宽度为 auto 时 占满父节点, 高度为 auto 时 由子级决定
     Block,
     /// 行排列, 子级 会按照一定规则 分割 父节点宽度, 高度最大为父节点的100%
     RowFlex,
     /// 列排列, 子级 会按照一定规则 分割 父节点高度, 宽度最大为父节点的100%
     ColFlex,
+    /// 网格排列, 子级 按照 行轨道和列轨道 放置到 网格区域中
+    Grid,
+    /// 层叠排列, 所有子级 都放置在 父节点的有效区域中, 后面的子级 覆盖前面的子级
+    Stack,
 }
 
 impl Default for Layout {
@@ -75,13 +92,384 @@ impl Default for Layout {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+/// 网格轨道尺寸
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackSize {
+    /// 固定尺寸, 支持 px 和 百分比
+    Fixed(Size),
+    /// 按比例 分配剩余空间
+    Fr(f32),
+    /// 由 轨道中子级的尺寸 决定
+    Auto,
+}
+
+impl From<&str> for TrackSize {
+    fn from(s: &str) -> Self {
+        let s = s.trim();
+        if s == "auto" {
+            Self::Auto
+        } else if let Some(fr) = s.strip_suffix("fr") {
+            Self::Fr(fr.parse::<f32>().unwrap_or(1.0))
+        } else {
+            Self::Fixed(Size::from(s))
+        }
+    }
+}
+
+/// 网格轨道, 尺寸 介于 min 和 max 之间
+/// 单个尺寸 等同于: "100" -> minmax(100, 100), "1fr" -> minmax(auto, 1fr), "auto" -> minmax(auto, auto)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridTrack {
+    pub min: TrackSize,
+    pub max: TrackSize,
+}
+
+impl Default for GridTrack {
+    fn default() -> Self {
+        Self {
+            min: TrackSize::Auto,
+            max: TrackSize::Auto,
+        }
+    }
+}
+
+impl From<TrackSize> for GridTrack {
+    fn from(size: TrackSize) -> Self {
+        match size {
+            TrackSize::Fr(_) => Self {
+                min: TrackSize::Auto,
+                max: size,
+            },
+            _ => Self {
+                min: size,
+                max: size,
+            },
+        }
+    }
+}
+
+/// 网格轨道列表
+/// 支持的格式: 空格分隔的 px/百分比/"1fr"/"auto", 以及 "minmax(min, max)" 和 "repeat(n, 轨道列表)"
+/// 如: "100 repeat(2, 1fr) minmax(50, 20%)"
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GridTemplate(pub Vec<GridTrack>);
+
+impl From<&str> for GridTemplate {
+    fn from(s: &str) -> Self {
+        let mut tracks = Vec::new();
+        for token in split_outside_brackets(s, char::is_whitespace) {
+            if let Some(inner) = token
+                .strip_prefix("minmax(")
+                .and_then(|t| t.strip_suffix(')'))
+            {
+                let (min, max) = inner.split_once(',').unwrap_or((inner, inner));
+                tracks.push(GridTrack {
+                    min: min.into(),
+                    max: max.into(),
+                });
+            } else if let Some(inner) = token
+                .strip_prefix("repeat(")
+                .and_then(|t| t.strip_suffix(')'))
+            {
+                if let Some((count, repeated)) = inner.split_once(',') {
+                    let count = count.trim().parse::<usize>().unwrap_or(1);
+                    let repeated = GridTemplate::from(repeated.trim());
+                    for _ in 0..count {
+                        tracks.extend_from_slice(&repeated.0);
+                    }
+                }
+            } else {
+                tracks.push(TrackSize::from(token).into());
+            }
+        }
+        Self(tracks)
+    }
+}
+
+/// 按 括号之外的分隔符 分割, 去掉 各部分首尾的空白 和 空的部分;
+/// 如 按空白分割 "calc(50% - 10) 20" 得到 "calc(50% - 10)" 和 "20"
+fn split_outside_brackets(s: &str, separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            c if depth == 0 && separator(c) => {
+                items.push(s[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    items.push(s[start..].trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// 子级在网格中的位置: 起始网格线 (从1开始) 和 跨越的轨道数
+/// 支持的格式: "2", "2 / 4", "2 / span 2", "span 2"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridPlacement {
+    /// None 表示 自动放置
+    pub start: Option<usize>,
+    pub span: usize,
+}
+
+impl Default for GridPlacement {
+    fn default() -> Self {
+        Self {
+            start: None,
+            span: 1,
+        }
+    }
+}
+
+impl From<&str> for GridPlacement {
+    fn from(s: &str) -> Self {
+        let parse_span = |s: &str| {
+            s.trim()
+                .strip_prefix("span")
+                .and_then(|n| n.trim().parse::<usize>().ok())
+        };
+        let parse_line = |s: &str| s.trim().parse::<usize>().ok().filter(|n| *n > 0);
+
+        let (start, end) = s.split_once('/').unwrap_or((s, ""));
+        if let Some(span) = parse_span(start) {
+            return Self {
+                start: None,
+                span: span.max(1),
+            };
+        }
+        let start = parse_line(start);
+        let span = match (start, parse_span(end), parse_line(end)) {
+            (_, Some(span), _) => span,
+            (Some(start), None, Some(end)) if end > start => end - start,
+            _ => 1,
+        };
+        Self {
+            start,
+            span: span.max(1),
+        }
+    }
+}
+
+impl From<usize> for GridPlacement {
+    fn from(start: usize) -> Self {
+        Self {
+            start: Some(start.max(1)),
+            span: 1,
+        }
+    }
+}
+
+/// 定位方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    /// 正常布局
+    Static,
+    /// 正常布局后, 根据 top/right/bottom/left 偏移, 不影响其他节点
+    Relative,
+    /// 脱离正常布局, 相对于 最近的非 Static 祖先节点 的有效区域 定位
+    Absolute,
+    /// 脱离正常布局, 相对于 窗口 定位
+    Fixed,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::Static
+    }
+}
+
+/// 层叠布局中 子级的对齐位置: 九个锚点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// 子级 在剩余空间中 的位置比例: 0.0 靠左/上, 0.5 居中, 1.0 靠右/下
+    pub fn factor(&self) -> Vec2 {
+        match self {
+            Self::TopLeft => vec2(0.0, 0.0),
+            Self::Top => vec2(0.5, 0.0),
+            Self::TopRight => vec2(1.0, 0.0),
+            Self::Left => vec2(0.0, 0.5),
+            Self::Center => vec2(0.5, 0.5),
+            Self::Right => vec2(1.0, 0.5),
+            Self::BottomLeft => vec2(0.0, 1.0),
+            Self::Bottom => vec2(0.5, 1.0),
+            Self::BottomRight => vec2(1.0, 1.0),
+        }
+    }
+}
+
+impl Default for Anchor {
+    fn default() -> Self {
+        Self::TopLeft
+    }
+}
+
+/// width/height 包括的范围
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoxSizing {
+    /// 只包括 内容
+    ContentBox,
+    /// 包括 内容, padding 和 border
+    BorderBox,
+}
+
+impl Default for BoxSizing {
+    fn default() -> Self {
+        Self::ContentBox
+    }
+}
+
+/// 子级 超出节点尺寸时 的处理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    /// 不裁剪, 子级 绘制到 节点之外
+    Visible,
+    /// 裁剪到 内边距盒子, 不响应 滚轮和拖拽
+    Hidden,
+    /// 裁剪到 内边距盒子, 可以滚动
+    Scroll,
+    /// 和 Scroll 相同, 内容没有超出时 不滚动
+    Auto,
+}
+
+impl Overflow {
+    /// 是否 裁剪子级
+    #[inline]
+    pub fn is_clip(&self) -> bool {
+        *self != Self::Visible
+    }
+
+    /// 是否 响应 滚轮和拖拽
+    #[inline]
+    pub fn is_scrollable(&self) -> bool {
+        matches!(self, Self::Scroll | Self::Auto)
+    }
+}
+
+impl Default for Overflow {
+    fn default() -> Self {
+        Self::Visible
+    }
+}
+
+/// 行内元素 在行盒中的 垂直对齐方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerticalAlign {
+    /// 基线对齐, 没有文本的元素 基线是 盒子的底边
+    Baseline,
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl Default for VerticalAlign {
+    fn default() -> Self {
+        Self::Baseline
+    }
+}
+
+/// 行盒中的子级 在水平方向上的 对齐方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl Default for TextAlign {
+    fn default() -> Self {
+        Self::Left
+    }
+}
+
+/// 弹性布局中, 子级 超出主轴尺寸时 是否换行
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlexWrap {
+    /// 不换行, 子级 会被压缩到一行
+    NoWrap,
+    /// 换行, 新的一行 排列在 交叉轴的后方
+    Wrap,
+    /// 换行, 新的一行 排列在 交叉轴的前方
+    WrapReverse,
+}
+
+impl Default for FlexWrap {
+    fn default() -> Self {
+        Self::NoWrap
+    }
+}
+
+/// 子级 在主轴方向上的 对齐方式;
+/// 块布局中 是垂直方向, 所有子级 作为一个整体对齐, SpaceBetween 等同于 FlexStart, 其余两端间隔 等同于 Center
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JustifyContent {
+    FlexStart,
+    FlexEnd,
+    Center,
+    /// 两端对齐, 子级之间的间隔相等
+    SpaceBetween,
+    /// 每个子级 两侧的间隔相等
+    SpaceAround,
+    /// 所有间隔 (包括两端) 都相等
+    SpaceEvenly,
+}
+
+impl Default for JustifyContent {
+    fn default() -> Self {
+        Self::FlexStart
+    }
+}
+
+/// 子级 在交叉轴方向上的 对齐方式;
+/// 块布局中 是水平方向, 只影响 比父节点窄的 块级子级
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlignItems {
+    FlexStart,
+    FlexEnd,
+    Center,
+    /// 未设置交叉轴尺寸 (为0) 的子级, 会拉伸到 整行的交叉轴尺寸
+    Stretch,
+}
+
+impl Default for AlignItems {
+    fn default() -> Self {
+        Self::Stretch
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Style {
     /* 盒子模型: */
     /* 总元素的宽度 = margin-left + border-left + width + padding-left + padding-right + border-right + margin-right */
     /* 总元素的高度 = margin-top + border-top + width + padding-top + padding-bottom + border-bottom + margin-bottom */
+    /// 默认为 auto
     pub width: Size,
     pub height: Size,
+    /// width/height 以及 尺寸约束 包括的范围, 默认为 ContentBox
+    pub box_sizing: BoxSizing,
+    /// 宽高比 (宽度 / 高度), 按 box_sizing 的范围计算; 宽度和高度 只确定一个时 由它计算另一个
+    pub aspect_ratio: Option<f32>,
+
+    /* 尺寸约束, None 表示 不限制 */
+    pub min_width: Option<Size>,
+    pub max_width: Option<Size>,
+    pub min_height: Option<Size>,
+    pub max_height: Option<Size>,
 
     pub padding: Quat,
     pub margin: Quat,
@@ -90,7 +478,210 @@ pub struct Style {
     pub border_radius: Quat,
     pub border_color: Color,
 
-    pub background_color: Color,
+    /// 纯色 或 渐变
+    pub background: Background,
+    /// 盒子阴影, 前面的 绘制在上层
+    pub box_shadow: Vec<BoxShadow>,
+
+    /* 弹性布局: 前5项作用于子级自身, 其余作用于 RowFlex/ColFlex 容器;
+    align_self/justify_content/align_items 也作用于 块布局 */
+    /// 剩余空间 按比例 分配给子级
+    pub flex_grow: f32,
+    /// 空间不足时 子级的压缩比例, 默认为 1.0
+    pub flex_shrink: f32,
+    /// 子级在主轴方向上的 初始尺寸, None 表示使用 width/height
+    pub flex_basis: Option<Size>,
+    /// 覆盖父节点的 align_items
+    pub align_self: Option<AlignItems>,
+    pub flex_wrap: FlexWrap,
+    pub justify_content: JustifyContent,
+    pub align_items: AlignItems,
+    /// 子级之间 以及 行之间的间隔, 也用于 网格布局
+    pub gap: Size,
+
+    /* 网格布局: 前2项作用于 Grid 容器, 后2项作用于子级自身 */
+    /// 列轨道, 如: "100 1fr minmax(50, 2fr) auto"
+    pub grid_template_columns: GridTemplate,
+    /// 行轨道, 行数不足时 自动添加 auto 行
+    pub grid_template_rows: GridTemplate,
+    pub grid_column: GridPlacement,
+    pub grid_row: GridPlacement,
+
+    /// 行内元素 在行盒中的 垂直对齐方式
+    pub vertical_align: VerticalAlign,
+    /// 作用于 容器: 行盒中的子级 在水平方向上的 对齐方式, 也用于 文本的各行
+    pub text_align: TextAlign,
+
+    /* 文本: 可继承, None 表示 继承父节点的计算值, 布局时 计算到 DomElement::inherited */
+    /// 字号
+    pub font_size: Option<f32>,
+    /// 行高 相对于 字号的倍数
+    pub line_height: Option<f32>,
+    /// 字族, 字重 和 样式, 用于 选择已加载的字体
+    pub font_family: Option<String>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    /// 文本颜色
+    pub color: Option<Color>,
+
+    /* 层叠布局: 第1项作用于 Stack 容器, 后2项作用于子级自身 */
+    /// 子级的 默认对齐位置
+    pub stack_align: Anchor,
+    /// 覆盖父节点的 stack_align
+    pub anchor: Option<Anchor>,
+    /// 宽度/高度为 auto 时 占满父节点的有效区域, 否则 收缩到内容
+    pub stretch: bool,
+
+    /* 定位: 偏移量 None 表示未设置 */
+    pub position: Position,
+    pub top: Option<Size>,
+    pub right: Option<Size>,
+    pub bottom: Option<Size>,
+    pub left: Option<Size>,
+
+    /// 子级 超出节点尺寸时 的处理方式
+    pub overflow: Overflow,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            width: Size::auto(),
+            height: Size::auto(),
+            box_sizing: BoxSizing::default(),
+            aspect_ratio: None,
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+            padding: Quat::default(),
+            margin: Quat::default(),
+            border_width: Quat::default(),
+            border_radius: Quat::default(),
+            border_color: Color::default(),
+            background: Background::default(),
+            box_shadow: Vec::new(),
+            flex_grow: 0.0,
+            flex_shrink: 1.0,
+            flex_basis: None,
+            align_self: None,
+            flex_wrap: FlexWrap::default(),
+            justify_content: JustifyContent::default(),
+            align_items: AlignItems::default(),
+            gap: Size::default(),
+            grid_template_columns: GridTemplate::default(),
+            grid_template_rows: GridTemplate::default(),
+            grid_column: GridPlacement::default(),
+            grid_row: GridPlacement::default(),
+            vertical_align: VerticalAlign::default(),
+            text_align: TextAlign::default(),
+            font_size: None,
+            line_height: None,
+            font_family: None,
+            font_weight: None,
+            font_style: None,
+            color: None,
+            stack_align: Anchor::default(),
+            anchor: None,
+            stretch: false,
+            position: Position::default(),
+            top: None,
+            right: None,
+            bottom: None,
+            left: None,
+            overflow: Overflow::default(),
+        }
+    }
+}
+
+/// 伪类: 节点的交互状态, 按 优先级从低到高 排列, 同时处于多个状态时 后面的状态样式 覆盖前面的
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PseudoClass {
+    /// 鼠标 位于节点 或 它的子级之上
+    Hover,
+    /// 最后一次 按下鼠标的节点
+    Focus,
+    /// 鼠标 在节点 或 它的子级上 按下, 还没有松开
+    Active,
+    /// 禁用的节点 不能激活 和 获得焦点, 也不接收事件
+    Disabled,
+}
+
+impl PseudoClass {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hover" => Some(Self::Hover),
+            "focus" => Some(Self::Focus),
+            "active" => Some(Self::Active),
+            "disabled" => Some(Self::Disabled),
+            _ => None,
+        }
+    }
+}
+
+/// 节点当前 处于的伪类状态
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PseudoState(u8);
+
+impl PseudoState {
+    #[inline]
+    pub fn contains(&self, class: PseudoClass) -> bool {
+        self.0 & (1 << class as u8) != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, class: PseudoClass, on: bool) {
+        if on {
+            self.0 |= 1 << class as u8;
+        } else {
+            self.0 &= !(1 << class as u8);
+        }
+    }
+}
+
+/// 可继承的文本属性 的计算值: 节点没有设置时 使用父节点的计算值, 根节点 使用默认值
+#[derive(Debug, Clone, PartialEq)]
+pub struct InheritedStyle {
+    /// 字号, 默认为 16 (根字体大小)
+    pub font_size: f32,
+    /// 行高 相对于 字号的倍数, 默认为 1.2
+    pub line_height: f32,
+    /// None 表示 不限制字族
+    pub font_family: Option<String>,
+    pub font_weight: FontWeight,
+    pub font_style: FontStyle,
+    /// 文本颜色, 默认为 黑色
+    pub color: Color,
+}
+
+impl Default for InheritedStyle {
+    fn default() -> Self {
+        Self {
+            font_size: 16.0,
+            line_height: 1.2,
+            font_family: None,
+            font_weight: FontWeight::default(),
+            font_style: FontStyle::default(),
+            color: Color::BLACK,
+        }
+    }
+}
+
+impl InheritedStyle {
+    /// 子节点的计算值: style 中 设置了的属性 覆盖 self
+    pub fn inherit(&self, style: &Style) -> Self {
+        Self {
+            font_size: style.font_size.unwrap_or(self.font_size),
+            line_height: style.line_height.unwrap_or(self.line_height),
+            font_family: style
+                .font_family
+                .clone()
+                .or_else(|| self.font_family.clone()),
+            font_weight: style.font_weight.unwrap_or(self.font_weight),
+            font_style: style.font_style.unwrap_or(self.font_style),
+            color: style.color.unwrap_or(self.color),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -107,109 +698,634 @@ pub struct DomElement {
     layout: Layout,
     /// 节点样式
     style: Style,
+    /// 可继承的文本属性 的计算值, 由布局计算
+    inherited: InheritedStyle,
     /// 节点样式 changed 标志
     dirty: bool,
     /// 节点在屏幕坐标系中的位置, 宽度和高度是 有效的宽度和高度, 不是盒子的宽度和高度
     ava_box: Box2,
     /// 父节点尺寸
     parent_size: Vec2,
+    /// 样式表 第一次层叠前 的样式和布局, 每次层叠 都从这里开始;
+    /// 之后 层叠之外 通过 setter 的修改 也写入这里, 不会被 下一次层叠 撤销
+    base: Option<Box<(Style, Layout)>>,
+    /// 正在层叠, 此时 setter 只修改 style, 不修改 base
+    cascading: bool,
+    /// 内联声明, 优先于 样式表, 可以引用 主题令牌
+    inline_style: Vec<Declaration>,
+    /// 交互状态, 以及 处于各个状态时 应用的声明
+    state: PseudoState,
+    state_styles: Vec<(PseudoClass, Vec<Declaration>)>,
+    /// 属性改变时的 过渡
+    transitions: Vec<TransitionState>,
+    /// 关键帧动画
+    animations: Animations,
+    /// 滚动偏移量, 子级 向左上 移动的距离
+    scroll_offset: Vec2,
+    /// 子级内容的尺寸 (相对于 有效区域的左上角), 由布局计算
+    scroll_size: Vec2,
 }
 
-impl DomElement {
-    pub fn new(tag: impl ToString) -> Self {
-        Self {
-            node_id: Id::next(),
-            id: String::new(),
-            class: Vec::new(),
-            tag: tag.to_string(),
-            layout: Layout::default(),
-            style: Style::default(),
-            dirty: true,
-            ava_box: Box2::default(),
-            parent_size: Vec2::default(),
-        }
+impl DomElement {
+    pub fn new(tag: impl ToString) -> Self {
+        Self {
+            node_id: Id::next(),
+            id: String::new(),
+            class: Vec::new(),
+            tag: tag.to_string(),
+            layout: Layout::default(),
+            style: Style::default(),
+            inherited: InheritedStyle::default(),
+            dirty: true,
+            ava_box: Box2::default(),
+            parent_size: Vec2::default(),
+            base: None,
+            cascading: false,
+            inline_style: Vec::new(),
+            state: PseudoState::default(),
+            state_styles: Vec::new(),
+            transitions: Vec::new(),
+            animations: Animations::default(),
+            scroll_offset: Vec2::default(),
+            scroll_size: Vec2::default(),
+        }
+    }
+
+    #[inline]
+    pub fn node_id(&self) -> Id {
+        self.node_id
+    }
+
+    #[inline]
+    pub fn tag(&self) -> &String {
+        &self.tag
+    }
+
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    pub fn set_id(&mut self, id: impl ToString) {
+        self.id = id.to_string();
+    }
+
+    #[inline]
+    pub fn class(&self) -> &[String] {
+        &self.class
+    }
+
+    /// 以空白分隔的 类名列表, 如: "card primary"
+    pub fn set_class(&mut self, class: &str) {
+        self.class = class.split_whitespace().map(str::to_string).collect();
+    }
+
+    pub fn add_class(&mut self, class: &str) {
+        if !self.has_class(class) {
+            self.class.push(class.to_string());
+        }
+    }
+
+    #[inline]
+    pub fn has_class(&self, class: &str) -> bool {
+        self.class.iter().any(|c| c == class)
+    }
+
+    #[inline]
+    pub fn inline_style(&self) -> &[Declaration] {
+        &self.inline_style
+    }
+
+    /// 设置内联声明, 层叠后 才生效
+    pub fn set_inline_style(&mut self, declarations: Vec<Declaration>) {
+        self.inline_style = declarations;
+    }
+
+    #[inline]
+    pub fn state(&self) -> PseudoState {
+        self.state
+    }
+
+    /// 设置交互状态, 返回 是否改变; 样式 在重新层叠后 才改变, 一般通过 App::set_state 设置
+    pub fn set_state(&mut self, class: PseudoClass, on: bool) -> bool {
+        let before = self.state;
+        self.state.set(class, on);
+        self.state != before
+    }
+
+    /// 处于 class 状态时 应用的声明, 优先于 样式表
+    pub fn set_state_style(&mut self, class: PseudoClass, declarations: Vec<Declaration>) {
+        self.state_styles.retain(|(c, _)| *c != class);
+        self.state_styles.push((class, declarations));
+        self.state_styles.sort_by_key(|(c, _)| *c);
+    }
+
+    /// property 改变时 从旧值 过渡到新值, 不能插值的属性 没有效果
+    pub fn set_transition(&mut self, property: Property, transition: Transition) {
+        match self
+            .transitions
+            .iter_mut()
+            .find(|state| state.property() == property)
+        {
+            Some(state) => state.set_transition(transition),
+            None => self
+                .transitions
+                .push(TransitionState::new(property, transition)),
+        }
+    }
+
+    /// 推进 过渡到 now, 插值结果 写入样式; 返回 是否还有 正在进行的过渡
+    pub fn update_transitions(&mut self, now: Duration) -> bool {
+        let mut running = false;
+        for transition in &mut self.transitions {
+            running |= transition.update(&mut self.style, now, &mut self.dirty);
+        }
+        running
+    }
+
+    pub fn add_animation(&mut self, animation: impl Into<AnimationHandle>) {
+        self.animations.add(animation.into());
+    }
+
+    #[inline]
+    pub fn animations(&self) -> &[AnimationHandle] {
+        self.animations.handles()
+    }
+
+    /// 推进 过渡和动画 到 now, 动画的值 覆盖 过渡的值; 刚结束的动画 加入 finished,
+    /// 返回 是否还有 正在进行的 过渡或动画
+    pub fn update_animations(
+        &mut self,
+        now: Duration,
+        finished: &mut Vec<AnimationHandle>,
+    ) -> bool {
+        if self.animations.is_empty() {
+            return self.update_transitions(now);
+        }
+        let before = self.style.clone();
+        self.animations.restore(&mut self.style);
+        let mut running = self.update_transitions(now);
+        running |= self.animations.apply(&mut self.style, now, finished);
+        if self.style != before {
+            self.dirty = true;
+        }
+        running
+    }
+
+    /// 当前状态下 需要应用的 状态样式声明, 按 伪类的优先级 排列
+    pub fn state_declarations(&self) -> impl Iterator<Item = &Declaration> {
+        self.state_styles
+            .iter()
+            .filter(|(class, _)| self.state.contains(*class))
+            .flat_map(|(_, declarations)| declarations)
+    }
+
+    /// 样式表层叠: 从 第一次层叠前的样式 开始, 依次应用 匹配的声明;
+    /// 样式表 覆盖 构建时设置的同名属性, 结果没有改变时 不标记为 dirty
+    pub fn cascade(&mut self, apply: impl FnOnce(&mut DomElement)) {
+        let (base_style, base_layout) = self
+            .base
+            .get_or_insert_with(|| Box::new((self.style.clone(), self.layout)))
+            .as_ref()
+            .clone();
+        let old_style = std::mem::replace(&mut self.style, base_style);
+        let old_layout = std::mem::replace(&mut self.layout, base_layout);
+        let old_dirty = self.dirty;
+        self.cascading = true;
+        apply(self);
+        self.cascading = false;
+        for transition in &mut self.transitions {
+            transition.retarget();
+        }
+        self.animations.retarget();
+        if self.style == old_style && self.layout == old_layout {
+            // 保留 布局计算的值
+            self.style = old_style;
+            self.dirty = old_dirty;
+        } else {
+            self.dirty = true;
+        }
+    }
+
+    /// 修改 样式的一个属性, 层叠之外的修改 同时写入 base
+    fn update_style<T: PartialEq + Clone>(&mut self, field: fn(&mut Style) -> &mut T, value: T) {
+        if let Some(base) = self.base.as_mut().filter(|_| !self.cascading) {
+            *field(&mut base.0) = value.clone();
+        }
+        update_if_changed(field(&mut self.style), value, &mut self.dirty);
+    }
+
+    #[inline]
+    pub fn set_layout(&mut self, layout: Layout) {
+        if let Some(base) = self.base.as_mut().filter(|_| !self.cascading) {
+            base.1 = layout;
+        }
+        update_if_changed(&mut self.layout, layout, &mut self.dirty);
+    }
+
+    #[inline]
+    pub fn set_style(&mut self, style: Style) {
+        if let Some(base) = self.base.as_mut().filter(|_| !self.cascading) {
+            base.0 = style.clone();
+        }
+        update_if_changed(&mut self.style, style, &mut self.dirty);
+    }
+
+    #[inline]
+    pub fn set_parent_size(&mut self, parent_size: Vec2) {
+        self.parent_size = parent_size;
+    }
+
+    #[inline]
+    pub fn set_ava_box(&mut self, ava_box: Box2) {
+        self.ava_box = ava_box;
+    }
+
+    #[inline]
+    pub fn set_width(&mut self, width: impl Into<Size>) {
+        self.update_style(|style| &mut style.width, width.into());
+    }
+
+    #[inline]
+    pub fn set_height(&mut self, height: impl Into<Size>) {
+        self.update_style(|style| &mut style.height, height.into());
+    }
+
+    #[inline]
+    pub fn set_min_width(&mut self, min_width: impl Into<Size>) {
+        self.update_style(|style| &mut style.min_width, Some(min_width.into()));
+    }
+
+    #[inline]
+    pub fn set_max_width(&mut self, max_width: impl Into<Size>) {
+        self.update_style(|style| &mut style.max_width, Some(max_width.into()));
+    }
+
+    #[inline]
+    pub fn set_min_height(&mut self, min_height: impl Into<Size>) {
+        self.update_style(|style| &mut style.min_height, Some(min_height.into()));
+    }
+
+    #[inline]
+    pub fn set_max_height(&mut self, max_height: impl Into<Size>) {
+        self.update_style(|style| &mut style.max_height, Some(max_height.into()));
+    }
+
+    #[inline]
+    pub fn set_box_sizing(&mut self, box_sizing: BoxSizing) {
+        self.update_style(|style| &mut style.box_sizing, box_sizing);
+    }
+
+    #[inline]
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.update_style(|style| &mut style.aspect_ratio, Some(aspect_ratio));
+    }
+
+    /// 根据 宽高比 和 内容高度 计算 内容宽度, 没有宽高比 返回 None
+    pub fn ratio_width(&self, height: f32) -> Option<f32> {
+        let edge = self.box_sizing_edge();
+        self.style
+            .aspect_ratio
+            .filter(|ratio| *ratio > 0.0)
+            .map(|ratio| ((height + edge.y) * ratio - edge.x).max(0.0))
+    }
+
+    /// 根据 宽高比 和 内容宽度 计算 内容高度, 没有宽高比 返回 None
+    pub fn ratio_height(&self, width: f32) -> Option<f32> {
+        let edge = self.box_sizing_edge();
+        self.style
+            .aspect_ratio
+            .filter(|ratio| *ratio > 0.0)
+            .map(|ratio| ((width + edge.x) / ratio - edge.y).max(0.0))
+    }
+
+    /// BorderBox 时 width/height 中 不属于内容的部分 (padding + border), ContentBox 时 为0
+    pub fn box_sizing_edge(&self) -> Vec2 {
+        match self.style.box_sizing {
+            BoxSizing::ContentBox => Vec2::ZERO,
+            BoxSizing::BorderBox => {
+                let border = self.border_width();
+                let padding = self.padding();
+                vec2(
+                    (border.left() + border.right() + padding.left() + padding.right()).into(),
+                    (border.top() + border.bottom() + padding.top() + padding.bottom()).into(),
+                )
+            }
+        }
+    }
+
+    /// 根据父节点宽度 计算 内容宽度, auto 返回 None
+    #[inline]
+    pub fn resolve_width(&self, parent_width: f32) -> Option<f32> {
+        let edge = self.box_sizing_edge().x;
+        self.with_units(|| self.style.width.resolve(parent_width))
+            .map(|width| (width - edge).max(0.0))
+    }
+
+    /// 根据父节点高度 计算 内容高度, auto 返回 None
+    #[inline]
+    pub fn resolve_height(&self, parent_height: f32) -> Option<f32> {
+        let edge = self.box_sizing_edge().y;
+        self.with_units(|| self.style.height.resolve(parent_height))
+            .map(|height| (height - edge).max(0.0))
+    }
+
+    /// 不依赖父节点尺寸的 内容宽度, 百分比和 auto 返回 None
+    #[inline]
+    pub fn fixed_width(&self) -> Option<f32> {
+        let edge = self.box_sizing_edge().x;
+        self.with_units(|| self.style.width.fixed())
+            .map(|width| (width - edge).max(0.0))
+    }
+
+    /// 不依赖父节点尺寸的 内容高度, 百分比和 auto 返回 None
+    #[inline]
+    pub fn fixed_height(&self) -> Option<f32> {
+        let edge = self.box_sizing_edge().y;
+        self.with_units(|| self.style.height.fixed())
+            .map(|height| (height - edge).max(0.0))
+    }
+
+    /// 把内容宽度 限制在 min_width 和 max_width 之间, 冲突时 min_width 优先
+    pub fn constrain_width(&self, width: f32, parent_width: f32) -> f32 {
+        self.with_units(|| {
+            constrain(
+                width,
+                self.style.min_width,
+                self.style.max_width,
+                parent_width,
+                self.box_sizing_edge().x,
+            )
+        })
+    }
+
+    /// 把内容高度 限制在 min_height 和 max_height 之间, 冲突时 min_height 优先
+    pub fn constrain_height(&self, height: f32, parent_height: f32) -> f32 {
+        self.with_units(|| {
+            constrain(
+                height,
+                self.style.min_height,
+                self.style.max_height,
+                parent_height,
+                self.box_sizing_edge().y,
+            )
+        })
+    }
+
+    #[inline]
+    pub fn set_padding(&mut self, padding: impl Into<Quat>) {
+        self.update_style(|style| &mut style.padding, padding.into());
+    }
+
+    #[inline]
+    pub fn set_margin(&mut self, margin: impl Into<Quat>) {
+        self.update_style(|style| &mut style.margin, margin.into());
+    }
+
+    #[inline]
+    pub fn set_border_width(&mut self, border_width: impl Into<Quat>) {
+        self.update_style(|style| &mut style.border_width, border_width.into());
+    }
+
+    #[inline]
+    pub fn set_border_radius(&mut self, border_radius: impl Into<Quat>) {
+        self.update_style(|style| &mut style.border_radius, border_radius.into());
+    }
+
+    #[inline]
+    pub fn set_border_color(&mut self, border_color: impl Into<Color>) {
+        self.update_style(|style| &mut style.border_color, border_color.into());
+    }
+
+    #[inline]
+    pub fn set_box_shadow(&mut self, box_shadow: Vec<BoxShadow>) {
+        self.update_style(|style| &mut style.box_shadow, box_shadow);
+    }
+
+    #[inline]
+    pub fn set_background_color(&mut self, background_color: impl Into<Color>) {
+        self.set_background(Background::Color(background_color.into()));
+    }
+
+    #[inline]
+    pub fn set_background(&mut self, background: impl Into<Background>) {
+        self.update_style(|style| &mut style.background, background.into());
+    }
+
+    #[inline]
+    pub fn set_flex_grow(&mut self, flex_grow: f32) {
+        self.update_style(|style| &mut style.flex_grow, flex_grow);
+    }
+
+    #[inline]
+    pub fn set_flex_shrink(&mut self, flex_shrink: f32) {
+        self.update_style(|style| &mut style.flex_shrink, flex_shrink);
+    }
+
+    #[inline]
+    pub fn set_flex_basis(&mut self, flex_basis: impl Into<Size>) {
+        self.update_style(|style| &mut style.flex_basis, Some(flex_basis.into()));
+    }
+
+    #[inline]
+    pub fn set_flex_wrap(&mut self, flex_wrap: FlexWrap) {
+        self.update_style(|style| &mut style.flex_wrap, flex_wrap);
+    }
+
+    #[inline]
+    pub fn set_justify_content(&mut self, justify_content: JustifyContent) {
+        self.update_style(|style| &mut style.justify_content, justify_content);
+    }
+
+    #[inline]
+    pub fn set_align_items(&mut self, align_items: AlignItems) {
+        self.update_style(|style| &mut style.align_items, align_items);
+    }
+
+    #[inline]
+    pub fn set_align_self(&mut self, align_self: AlignItems) {
+        self.update_style(|style| &mut style.align_self, Some(align_self));
+    }
+
+    #[inline]
+    pub fn set_gap(&mut self, gap: impl Into<Size>) {
+        self.update_style(|style| &mut style.gap, gap.into());
+    }
+
+    #[inline]
+    pub fn set_grid_template_columns(&mut self, columns: impl Into<GridTemplate>) {
+        self.update_style(|style| &mut style.grid_template_columns, columns.into());
+    }
+
+    #[inline]
+    pub fn set_grid_template_rows(&mut self, rows: impl Into<GridTemplate>) {
+        self.update_style(|style| &mut style.grid_template_rows, rows.into());
+    }
+
+    #[inline]
+    pub fn set_grid_column(&mut self, column: impl Into<GridPlacement>) {
+        self.update_style(|style| &mut style.grid_column, column.into());
+    }
+
+    #[inline]
+    pub fn set_grid_row(&mut self, row: impl Into<GridPlacement>) {
+        self.update_style(|style| &mut style.grid_row, row.into());
+    }
+
+    #[inline]
+    pub fn set_vertical_align(&mut self, vertical_align: VerticalAlign) {
+        self.update_style(|style| &mut style.vertical_align, vertical_align);
+    }
+
+    #[inline]
+    pub fn set_text_align(&mut self, text_align: TextAlign) {
+        self.update_style(|style| &mut style.text_align, text_align);
+    }
+
+    #[inline]
+    pub fn set_font_size(&mut self, font_size: f32) {
+        self.update_style(|style| &mut style.font_size, Some(font_size));
+    }
+
+    #[inline]
+    pub fn set_line_height(&mut self, line_height: f32) {
+        self.update_style(|style| &mut style.line_height, Some(line_height));
+    }
+
+    #[inline]
+    pub fn set_font_family(&mut self, font_family: impl ToString) {
+        self.update_style(
+            |style| &mut style.font_family,
+            Some(font_family.to_string()),
+        );
+    }
+
+    #[inline]
+    pub fn set_font_weight(&mut self, font_weight: impl Into<FontWeight>) {
+        self.update_style(|style| &mut style.font_weight, Some(font_weight.into()));
+    }
+
+    #[inline]
+    pub fn set_font_style(&mut self, font_style: impl Into<FontStyle>) {
+        self.update_style(|style| &mut style.font_style, Some(font_style.into()));
+    }
+
+    #[inline]
+    pub fn set_color(&mut self, color: impl Into<Color>) {
+        self.update_style(|style| &mut style.color, Some(color.into()));
+    }
+
+    #[inline]
+    pub fn set_stack_align(&mut self, stack_align: Anchor) {
+        self.update_style(|style| &mut style.stack_align, stack_align);
+    }
+
+    #[inline]
+    pub fn set_anchor(&mut self, anchor: Anchor) {
+        self.update_style(|style| &mut style.anchor, Some(anchor));
+    }
+
+    #[inline]
+    pub fn set_stretch(&mut self, stretch: bool) {
+        self.update_style(|style| &mut style.stretch, stretch);
+    }
+
+    #[inline]
+    pub fn set_position(&mut self, position: Position) {
+        self.update_style(|style| &mut style.position, position);
     }
 
     #[inline]
-    pub fn node_id(&self) -> Id {
-        self.node_id
+    pub fn set_top(&mut self, top: impl Into<Size>) {
+        self.update_style(|style| &mut style.top, Some(top.into()));
     }
 
     #[inline]
-    pub fn tag(&self) -> &String {
-        &self.tag
+    pub fn set_right(&mut self, right: impl Into<Size>) {
+        self.update_style(|style| &mut style.right, Some(right.into()));
     }
 
     #[inline]
-    pub fn set_layout(&mut self, layout: Layout) {
-        self.layout = layout;
-        self.dirty = true;
+    pub fn set_bottom(&mut self, bottom: impl Into<Size>) {
+        self.update_style(|style| &mut style.bottom, Some(bottom.into()));
     }
 
     #[inline]
-    pub fn set_style(&mut self, style: Style) {
-        self.style = style;
-        self.dirty = true;
+    pub fn set_left(&mut self, left: impl Into<Size>) {
+        self.update_style(|style| &mut style.left, Some(left.into()));
     }
 
     #[inline]
-    pub fn set_parent_size(&mut self, parent_size: Vec2) {
-        self.parent_size = parent_size;
-        self.dirty = true;
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.update_style(|style| &mut style.overflow, overflow);
     }
 
     #[inline]
-    pub fn set_ava_box(&mut self, ava_box: Box2) {
-        self.ava_box = ava_box;
-        self.dirty = true;
+    pub fn scroll_offset(&self) -> Vec2 {
+        self.scroll_offset
     }
 
+    /// 设置滚动偏移量, 限制在 0 和 max_scroll_offset 之间
     #[inline]
-    pub fn set_width(&mut self, width: impl Into<Size>) {
-        self.style.width = width.into();
-        self.dirty = true;
+    pub fn set_scroll_offset(&mut self, scroll_offset: Vec2) {
+        let scroll_offset = scroll_offset.clamp(Vec2::ZERO, self.max_scroll_offset());
+        update_if_changed(&mut self.scroll_offset, scroll_offset, &mut self.dirty);
+    }
+
+    /// 滚动 delta 距离, 返回 偏移量是否改变
+    pub fn scroll_by(&mut self, delta: Vec2) -> bool {
+        let old = self.scroll_offset;
+        self.set_scroll_offset(old + delta);
+        self.scroll_offset != old
     }
 
     #[inline]
-    pub fn set_height(&mut self, height: impl Into<Size>) {
-        self.style.height = height.into();
-        self.dirty = true;
+    pub fn scroll_size(&self) -> Vec2 {
+        self.scroll_size
     }
 
+    /// 布局结果: 更新 子级内容的尺寸, 并重新限制 滚动偏移量
     #[inline]
-    pub fn set_padding(&mut self, padding: impl Into<Quat>) {
-        self.style.padding = padding.into();
-        self.dirty = true;
+    pub fn set_scroll_size(&mut self, scroll_size: Vec2) {
+        self.scroll_size = scroll_size;
+        self.scroll_offset = self
+            .scroll_offset
+            .clamp(Vec2::ZERO, self.max_scroll_offset());
     }
 
+    /// 最大滚动偏移量: 子级内容 超出 有效区域 的部分
     #[inline]
-    pub fn set_margin(&mut self, margin: impl Into<Quat>) {
-        self.style.margin = margin.into();
-        self.dirty = true;
+    pub fn max_scroll_offset(&self) -> Vec2 {
+        (self.scroll_size - self.size()).max(Vec2::ZERO)
     }
 
+    /// 布局: 根据父节点尺寸 更新 margin/padding/border 的计算值
     #[inline]
-    pub fn set_border_width(&mut self, border_width: impl Into<Quat>) {
-        self.style.border_width = border_width.into();
-        self.dirty = true;
+    pub fn update_edges(&mut self, parent_size: Vec2) {
+        let style = &mut self.style;
+        Units::with_font_size(self.inherited.font_size, || {
+            style.margin = style.margin.update(parent_size);
+            style.padding = style.padding.update(parent_size);
+            style.border_width = style.border_width.update(parent_size);
+        });
     }
 
+    /// 布局结果: 只更新宽度的计算值, 保留参数值 (如百分比), 以便下次布局 重新计算
     #[inline]
-    pub fn set_border_radius(&mut self, border_radius: impl Into<Quat>) {
-        self.style.border_radius = border_radius.into();
-        self.dirty = true;
+    pub fn set_computed_width(&mut self, width: f32) {
+        self.style.width.set_value(width);
     }
 
+    /// 布局结果: 只更新高度的计算值, 保留参数值
     #[inline]
-    pub fn set_border_color(&mut self, border_color: impl Into<Color>) {
-        self.style.border_color = border_color.into();
-        self.dirty = true;
+    pub fn set_computed_height(&mut self, height: f32) {
+        self.style.height.set_value(height);
     }
 
     #[inline]
-    pub fn set_background_color(&mut self, background_color: impl Into<Color>) {
-        self.style.background_color = background_color.into();
-        self.dirty = true;
+    pub fn style(&self) -> &Style {
+        &self.style
     }
 
     #[inline]
@@ -217,6 +1333,23 @@ impl DomElement {
         self.layout
     }
 
+    /// 可继承的文本属性 的计算值, 布局后 才有效
+    #[inline]
+    pub fn inherited(&self) -> &InheritedStyle {
+        &self.inherited
+    }
+
+    /// 换算 自身的尺寸: em 相对于 自身继承的 字体大小
+    pub fn with_units<T>(&self, f: impl FnOnce() -> T) -> T {
+        Units::with_font_size(self.inherited.font_size, f)
+    }
+
+    /// 从父节点的计算值 继承文本属性, 计算值改变时 标记为 dirty
+    pub fn inherit(&mut self, parent: &InheritedStyle) {
+        let inherited = parent.inherit(&self.style);
+        update_if_changed(&mut self.inherited, inherited, &mut self.dirty);
+    }
+
     #[inline]
     pub fn parent_size(&self) -> Vec2 {
         self.parent_size
@@ -235,7 +1368,7 @@ impl DomElement {
     #[inline]
     pub fn update_width(&mut self, parent_width: f32) {
         let width = &mut self.style.width;
-        *width = width.update(parent_width);
+        *width = Units::with_font_size(self.inherited.font_size, || width.update(parent_width));
     }
 
     #[inline]
@@ -246,7 +1379,7 @@ impl DomElement {
     #[inline]
     pub fn update_height(&mut self, parent_hight: f32) {
         let height = &mut self.style.height;
-        *height = height.update(parent_hight);
+        *height = Units::with_font_size(self.inherited.font_size, || height.update(parent_hight));
     }
 
     pub fn size(&self) -> Vec2 {
@@ -303,6 +1436,15 @@ impl DomElement {
         box2(start, end)
     }
 
+    /// 内边距盒子: 有效区域 加上 padding, overflow 裁剪到这个区域
+    #[inline]
+    pub fn padding_box(&self) -> Box2 {
+        let padding = self.padding();
+        let start = self.ava_box.min - vec2(padding.left().into(), padding.top().into());
+        let end = self.ava_box.max + vec2(padding.right().into(), padding.bottom().into());
+        box2(start, end)
+    }
+
     #[inline]
     pub fn box_width(&self) -> f32 {
         self.width() + self.edge_width()
@@ -339,8 +1481,13 @@ impl DomElement {
     }
 
     #[inline]
-    pub fn background_color(&self) -> Color {
-        self.style.background_color
+    pub fn background(&self) -> &Background {
+        &self.style.background
+    }
+
+    #[inline]
+    pub fn box_shadow(&self) -> &[BoxShadow] {
+        &self.style.box_shadow
     }
 
     #[inline]
@@ -354,6 +1501,35 @@ impl DomElement {
     }
 }
 
+/// 值改变时 才更新, 并标记为 dirty; Size 只比较参数值, 不比较 布局计算的值
+#[inline]
+fn update_if_changed<T: PartialEq>(field: &mut T, value: T, dirty: &mut bool) {
+    if *field != value {
+        *field = value;
+        *dirty = true;
+    }
+}
+
+/// parent 为0 (父节点尺寸未知) 时, 只使用 不依赖父节点尺寸的约束; 约束值 减去 edge 后 才是内容尺寸
+fn constrain(value: f32, min: Option<Size>, max: Option<Size>, parent: f32, edge: f32) -> f32 {
+    let resolve = |size: Size| {
+        let size = if parent > 0.0 {
+            size.resolve(parent)
+        } else {
+            size.fixed()
+        };
+        size.map(|size| (size - edge).max(0.0))
+    };
+    let mut value = value;
+    if let Some(max) = max.and_then(resolve) {
+        value = value.min(max);
+    }
+    if let Some(min) = min.and_then(resolve) {
+        value = value.max(min);
+    }
+    value
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Value {
     Quat(f32, f32, f32, f32),
@@ -396,12 +1572,139 @@ impl From<(f32, f32)> for Value {
     }
 }
 
-/// 支持的格式: "10%" 表示10%比例， "10.0" 表示10.0px
-/// 百分比是相对于 父节点尺寸 (DomElement.parent_size)
-#[derive(Debug, Clone, Copy)]
+/// 支持的格式: "10%" 表示10%比例， "10.0" 表示10.0px, "auto" 表示由布局决定,
+/// 单位: px (默认), em, rem, vw, vh, dp, 如: "2rem", "50vw", "16dp";
+/// "100% - 60" 或 "calc(50% + 1em)" 表示 不同单位的混合表达式, 支持 + - * / 和括号
+/// 百分比是相对于 父节点尺寸 (DomElement.parent_size), 其他单位 根据 Units 换算
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum InnerSize {
     Percent(f32),
     Number(f32),
+    Auto,
+    /// 表达式, 或者 px和百分比 以外的单位
+    Calc(SizeExpr),
+}
+
+/// 单位换算, 布局时 由 LayoutEngine 设置为 当前线程的值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Units {
+    /// 窗口尺寸, 1vw = 窗口宽度的 1/100, 1vh = 窗口高度的 1/100
+    pub viewport: Vec2,
+    /// 窗口的缩放比例, 1dp = scale_factor px
+    pub scale_factor: f32,
+    /// 根字体大小, 1rem = root_font_size px
+    pub root_font_size: f32,
+    /// 当前字体大小, 1em = font_size px
+    pub font_size: f32,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            viewport: Vec2::ZERO,
+            scale_factor: 1.0,
+            root_font_size: 16.0,
+            font_size: 16.0,
+        }
+    }
+}
+
+thread_local! {
+    static UNITS: Cell<Units> = Cell::new(Units::default());
+}
+
+impl Units {
+    /// 当前线程 换算单位 使用的值
+    #[inline]
+    pub fn current() -> Self {
+        UNITS.with(|units| units.get())
+    }
+
+    #[inline]
+    pub fn set_current(units: Self) {
+        UNITS.with(|current| current.set(units));
+    }
+
+    /// 当前字体大小 为 font_size 时 执行 f, 之后 恢复
+    pub fn with_font_size<T>(font_size: f32, f: impl FnOnce() -> T) -> T {
+        let units = Self::current();
+        Self::set_current(Self { font_size, ..units });
+        let result = f();
+        Self::set_current(units);
+        result
+    }
+}
+
+/// Size 表达式 化简后的结果: 各个单位的系数
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct SizeExpr {
+    percent: f32,
+    px: f32,
+    em: f32,
+    rem: f32,
+    vw: f32,
+    vh: f32,
+    dp: f32,
+}
+
+impl SizeExpr {
+    /// 只有 px 时 是纯数字, 可以作为 乘除法的系数
+    fn as_number(&self) -> Option<f32> {
+        let number = Self {
+            px: self.px,
+            ..Self::default()
+        };
+        (*self == number).then_some(self.px)
+    }
+
+    /// 只有 百分比
+    fn as_percent(&self) -> Option<f32> {
+        let percent = Self {
+            percent: self.percent,
+            ..Self::default()
+        };
+        (*self == percent).then_some(self.percent)
+    }
+
+    /// 不依赖 父节点尺寸 的部分, 换算为 px
+    fn absolute(&self, units: &Units) -> f32 {
+        self.px
+            + self.em * units.font_size
+            + self.rem * units.root_font_size
+            + self.vw * units.viewport.x / 100.0
+            + self.vh * units.viewport.y / 100.0
+            + self.dp * units.scale_factor
+    }
+}
+
+impl Add for SizeExpr {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            percent: self.percent + other.percent,
+            px: self.px + other.px,
+            em: self.em + other.em,
+            rem: self.rem + other.rem,
+            vw: self.vw + other.vw,
+            vh: self.vh + other.vh,
+            dp: self.dp + other.dp,
+        }
+    }
+}
+
+impl Mul<f32> for SizeExpr {
+    type Output = Self;
+    fn mul(self, k: f32) -> Self {
+        Self {
+            percent: self.percent * k,
+            px: self.px * k,
+            em: self.em * k,
+            rem: self.rem * k,
+            vw: self.vw * k,
+            vh: self.vh * k,
+            dp: self.dp * k,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -434,9 +1737,46 @@ impl Size {
         }
     }
 
-    /// 在布局后, 会使用有效值 更新实际的 Size.value
+    /// 由布局决定的尺寸, 如: 块元素的宽度 占满父节点, 高度 由子级决定
+    #[inline]
+    pub fn auto() -> Self {
+        Self {
+            param: InnerSize::Auto,
+            value: 0.0,
+        }
+    }
+
+    #[inline]
+    pub fn is_auto(&self) -> bool {
+        matches!(self.param, InnerSize::Auto)
+    }
+
+    /// 不依赖父节点尺寸的值, 百分比和 auto 返回 None
+    #[inline]
+    pub fn fixed(&self) -> Option<f32> {
+        match self.param {
+            InnerSize::Number(n) => Some(n),
+            InnerSize::Calc(expr) if expr.percent == 0.0 => {
+                Some(expr.absolute(&Units::current()).max(0.0))
+            }
+            _ => None,
+        }
+    }
+
+    /// 根据父节点尺寸 计算值, auto 返回 None
+    #[inline]
+    pub fn resolve(self, max_value: f32) -> Option<f32> {
+        if self.is_auto() {
+            None
+        } else {
+            Some(self.update(max_value).value())
+        }
+    }
+
+    /// 在布局后, 会使用有效值 更新实际的 Size.value, auto 保留 布局计算的值
     pub fn update(mut self, max_value: f32) -> Self {
         let value = match self.param {
+            InnerSize::Auto => self.value,
             InnerSize::Number(n) => {
                 if max_value == 0.0 {
                     n
@@ -445,6 +1785,14 @@ impl Size {
                 }
             }
             InnerSize::Percent(p) => max_value.minimum(p * max_value),
+            InnerSize::Calc(expr) => {
+                let value = (expr.percent * max_value + expr.absolute(&Units::current())).max(0.0);
+                if max_value == 0.0 {
+                    value
+                } else {
+                    max_value.minimum(value)
+                }
+            }
         };
         self.value = value;
         self
@@ -454,6 +1802,48 @@ impl Size {
     pub fn value(&self) -> f32 {
         self.value
     }
+
+    /// 直接设置计算值, 不改变参数值
+    #[inline]
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value;
+    }
+
+    /// 参数值的 线性插值, t 为 0 时是 self, 为 1 时是 other; auto 不能插值, 返回 None
+    pub fn lerp(&self, other: &Self, t: f32) -> Option<Self> {
+        Some(Self::from_expr(
+            self.expr()? * (1.0 - t) + other.expr()? * t,
+        ))
+    }
+
+    fn expr(&self) -> Option<SizeExpr> {
+        match self.param {
+            InnerSize::Number(px) => Some(SizeExpr {
+                px,
+                ..SizeExpr::default()
+            }),
+            InnerSize::Percent(percent) => Some(SizeExpr {
+                percent,
+                ..SizeExpr::default()
+            }),
+            InnerSize::Auto => None,
+            InnerSize::Calc(expr) => Some(expr),
+        }
+    }
+
+    /// 化简: 只有 px 或 只有百分比 的表达式 保存为 数字 或 百分比
+    fn from_expr(expr: SizeExpr) -> Self {
+        if let Some(number) = expr.as_number() {
+            Self::new_value(number)
+        } else if let Some(percent) = expr.as_percent() {
+            Self::new_percent(percent)
+        } else {
+            Self {
+                param: InnerSize::Calc(expr),
+                value: 0.0,
+            }
+        }
+    }
 }
 
 impl Default for Size {
@@ -465,14 +1855,151 @@ impl Default for Size {
     }
 }
 
+/// 格式化为 可以再次解析的字符串, 如: "10", "50%", "auto", "calc(50% + 2em)"
+impl std::fmt::Display for Size {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.param {
+            InnerSize::Number(number) => write!(f, "{}", number),
+            InnerSize::Percent(percent) => write!(f, "{}%", percent * 100.0),
+            InnerSize::Auto => write!(f, "auto"),
+            InnerSize::Calc(expr) => {
+                let terms = [
+                    (expr.px, "px"),
+                    (expr.percent * 100.0, "%"),
+                    (expr.em, "em"),
+                    (expr.rem, "rem"),
+                    (expr.vw, "vw"),
+                    (expr.vh, "vh"),
+                    (expr.dp, "dp"),
+                ]
+                .iter()
+                .filter(|(k, _)| *k != 0.0)
+                .map(|(k, unit)| format!("{}{}", k, unit))
+                .collect::<Vec<_>>();
+                if terms.is_empty() {
+                    write!(f, "0")
+                } else {
+                    write!(f, "calc({})", terms.join(" + "))
+                }
+            }
+        }
+    }
+}
+
 impl From<&str> for Size {
     fn from(s: &str) -> Self {
-        if s.ends_with("%") {
-            let percent = s[..s.len() - 1].parse::<f32>().unwrap_or(0.0) / 100.0;
-            Self::new_percent(percent)
-        } else {
-            let num = s.parse::<f32>().unwrap_or(0.0);
-            Self::new_value(num)
+        let s = s.trim();
+        if s == "auto" {
+            return Self::auto();
+        }
+        Self::from_expr(CalcParser::new(s).parse().unwrap_or_default())
+    }
+}
+
+/// 解析 Size 表达式, 结果化简为 各个单位的系数
+/// 乘法 至少有一边 是不带单位的数字, 除数 必须是 不带单位的数字
+struct CalcParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> CalcParser<'a> {
+    fn new(s: &'a str) -> Self {
+        let input = s
+            .strip_prefix("calc")
+            .map(str::trim_start)
+            .filter(|s| s.starts_with('('))
+            .unwrap_or(s);
+        Self { input, pos: 0 }
+    }
+
+    fn parse(mut self) -> Option<SizeExpr> {
+        let result = self.expr()?;
+        self.skip_whitespace();
+        (self.pos == self.input.len()).then_some(result)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.pos..].chars().next()
+    }
+
+    /// expr = term (('+' | '-') term)*
+    fn expr(&mut self) -> Option<SizeExpr> {
+        let mut left = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.pos += 1;
+            let right = self.term()?;
+            left = if op == '+' {
+                left + right
+            } else {
+                left + right * -1.0
+            };
+        }
+        Some(left)
+    }
+
+    /// term = factor (('*' | '/') factor)*
+    fn term(&mut self) -> Option<SizeExpr> {
+        let mut left = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.pos += 1;
+            let right = self.factor()?;
+            left = match (op, left.as_number(), right.as_number()) {
+                ('*', _, Some(k)) => left * k,
+                ('*', Some(k), _) => right * k,
+                ('/', _, Some(k)) if k != 0.0 => left * (1.0 / k),
+                _ => return None,
+            };
+        }
+        Some(left)
+    }
+
+    /// factor = '-' factor | '(' expr ')' | number unit?
+    fn factor(&mut self) -> Option<SizeExpr> {
+        match self.peek()? {
+            '-' => {
+                self.pos += 1;
+                self.factor().map(|expr| expr * -1.0)
+            }
+            '(' => {
+                self.pos += 1;
+                let result = self.expr()?;
+                if self.peek()? != ')' {
+                    return None;
+                }
+                self.pos += 1;
+                Some(result)
+            }
+            _ => {
+                let rest = &self.input[self.pos..];
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(rest.len());
+                let num = rest[..len].parse::<f32>().ok()?;
+                let unit_len = rest[len..]
+                    .find(|c: char| !(c.is_ascii_alphabetic() || c == '%'))
+                    .unwrap_or(rest.len() - len);
+                self.pos += len + unit_len;
+
+                let mut expr = SizeExpr::default();
+                match &rest[len..len + unit_len] {
+                    "" | "px" => expr.px = num,
+                    "%" => expr.percent = num / 100.0,
+                    "em" => expr.em = num,
+                    "rem" => expr.rem = num,
+                    "vw" => expr.vw = num,
+                    "vh" => expr.vh = num,
+                    "dp" => expr.dp = num,
+                    _ => return None,
+                }
+                Some(expr)
+            }
         }
     }
 }
@@ -509,18 +2036,36 @@ impl AddAssign for Size {
     }
 }
 
+/// 只比较参数值: 布局计算的值 不影响 样式是否改变
 impl PartialEq for Size {
     fn eq(&self, other: &Self) -> bool {
-        self.value == other.value
+        self.param == other.param
     }
 }
 
+/// 参数值相同时 相等, 和 PartialEq 一致; 否则 按计算值 比较, 计算值相同时 无法比较
 impl PartialOrd for Size {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.value.partial_cmp(&other.value)
+        if self == other {
+            return Some(std::cmp::Ordering::Equal);
+        }
+        self.value
+            .partial_cmp(&other.value)
+            .filter(|ordering| ordering.is_ne())
     }
 }
 
+#[test]
+fn test_size_ordering() {
+    // 比较 和 相等 一致: 参数值不同 计算值相同的 尺寸 无法比较
+    let percent = Size::from("50%");
+    assert_eq!(percent.value(), 0.0);
+    assert_ne!(percent, Size::from(0.0));
+    assert_eq!(percent.partial_cmp(&Size::from(0.0)), None);
+    assert!(Size::from(5.0) < Size::from(10.0));
+    assert!(Size::from("calc(1 + 1)") <= Size::from("calc(1 + 1)"));
+}
+
 pub trait Convert {
     fn convert_size(self) -> Box2;
 }
@@ -538,10 +2083,19 @@ impl Convert for Box2 {
 /// 支持的格式: 1个/2个/3个/4个 Size 类型的数据组成的字符串.
 /// 如: "50.1% 10.0 20.0 10.0" 表示 上: 50%, 右: 10.0px, 下: 20.0px, 左: 10.0px
 /// 如: "10.0 20.0" 表示 上下为: 10.0, 左右: 10.0
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Quat(pub [Size; 4]);
 
 impl Quat {
+    /// 四个边 分别插值, 有 auto 时 返回 None
+    pub fn lerp(&self, other: &Self, t: f32) -> Option<Self> {
+        let mut result = *self;
+        for (size, other) in result.0.iter_mut().zip(other.0.iter()) {
+            *size = size.lerp(other, t)?;
+        }
+        Some(result)
+    }
+
     pub fn update(mut self, max_size: Vec2) -> Self {
         self.0[0] = self.0[0].update(max_size.x);
         self.0[1] = self.0[1].update(max_size.y);
@@ -580,11 +2134,14 @@ impl From<&str> for Quat {
     fn from(s: &str) -> Self {
         // 上 右 下 左
         let mut numbers = [Size::default(); 4];
-        let values = s
-            .split_whitespace()
-            .map(|c| Size::from(c))
+        // calc 表达式中 可以有空白
+        let values = split_outside_brackets(s, char::is_whitespace)
+            .into_iter()
+            .map(Size::from)
             .collect::<Vec<_>>();
-        if values.len() == 1 {
+        if values.is_empty() {
+            return Self::default();
+        } else if values.len() == 1 {
             // 上下左右 一样
             numbers[0] = values[0];
             numbers[1] = values[0];
@@ -620,3 +2177,289 @@ impl From<f32> for Quat {
         Self([num; 4])
     }
 }
+
+/// 盒子阴影, 格式: [inset] x偏移 y偏移 [模糊半径 [扩展距离]] 颜色, 长度的单位为 px
+/// 如: "0 4 12 #00000040", "inset 0 1 2 1 #000000"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxShadow {
+    pub offset: Vec2,
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Color,
+    /// 内阴影 绘制在 背景之上, 盒子之内
+    pub inset: bool,
+}
+
+impl Default for BoxShadow {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            blur: 0.0,
+            spread: 0.0,
+            color: Color::BLACK,
+            inset: false,
+        }
+    }
+}
+
+impl BoxShadow {
+    /// 逗号分隔的 多个阴影, 前面的 绘制在上层; "none" 表示 没有阴影
+    pub fn parse_list(s: &str) -> Vec<Self> {
+        let s = s.trim();
+        if s.is_empty() || s == "none" {
+            return Vec::new();
+        }
+        split_outside_brackets(s, |c| c == ',')
+            .into_iter()
+            .map(Self::from)
+            .collect()
+    }
+
+    /// 投在 rect 上的阴影, border_radius 是 rect 的圆角半径
+    pub fn to_shadow(&self, rect: Rect, border_radius: f32) -> Shadow {
+        Shadow {
+            offset: self.offset.into(),
+            blur: self.blur,
+            spread: self.spread,
+            border_radius,
+            inset: if self.inset { 1.0 } else { 0.0 },
+            ..Shadow::new(rect, self.color)
+        }
+    }
+}
+
+impl From<&str> for BoxShadow {
+    fn from(s: &str) -> Self {
+        let mut shadow = Self::default();
+        let mut rest = s.trim();
+        if let Some(s) = rest.strip_prefix("inset") {
+            shadow.inset = true;
+            rest = s.trim_start();
+        }
+        // 第一个 不是数字的部分 是颜色
+        let color_start = rest.find(['#', '(', '[']).unwrap_or(rest.len());
+        let lengths = rest[..color_start]
+            .split_whitespace()
+            .map(|s| s.trim_end_matches("px").parse::<f32>().unwrap_or(0.0))
+            .collect::<Vec<_>>();
+        match lengths[..] {
+            [x, y, ref rest @ ..] => {
+                shadow.offset = vec2(x, y);
+                shadow.blur = rest.first().copied().unwrap_or(0.0).max(0.0);
+                shadow.spread = rest.get(1).copied().unwrap_or(0.0);
+            }
+            _ => tracing::warn!("无效的阴影: {:?}", s),
+        }
+        if color_start < rest.len() {
+            shadow.color = Color::from(rest[color_start..].trim());
+        }
+        shadow
+    }
+}
+
+/// 背景: 纯色, 或 线性/径向/锥形 渐变, 格式同 CSS, 如:
+/// "#ff0000", "linear-gradient(90deg, #ff0000, #0000ff 80%)", "linear-gradient(to right, ...)",
+/// "radial-gradient(at 50% 0%, #ffffff, #000000)", "conic-gradient(from 45deg, ...)";
+/// 色标 "颜色 [位置%]" 省略位置时 在相邻的位置之间 均匀分布
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+    Color(Color),
+    Gradient(Gradient),
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::Color(Color::default())
+    }
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Self {
+        Self::Color(color)
+    }
+}
+
+impl From<Gradient> for Background {
+    fn from(gradient: Gradient) -> Self {
+        Self::Gradient(gradient)
+    }
+}
+
+impl From<&str> for Background {
+    fn from(s: &str) -> Self {
+        let s = s.trim();
+        let (kind, args) = match s.split_once('(') {
+            Some((name, args)) if name.ends_with("-gradient") && args.ends_with(')') => {
+                (name, &args[..args.len() - 1])
+            }
+            _ => return Self::Color(Color::from(s)),
+        };
+        let mut args = split_outside_brackets(args, |c| c == ',');
+        let first = args.first().copied().unwrap_or_default();
+        let mut gradient = match kind {
+            "linear-gradient" => {
+                let angle = parse_direction(first);
+                Gradient::linear(angle.unwrap_or(180.0))
+            }
+            "radial-gradient" => {
+                let center = first.strip_prefix("at").map(parse_center);
+                let mut gradient = Gradient::radial();
+                if let Some(center) = center {
+                    gradient.kind = GradientKind::Radial { center };
+                }
+                gradient
+            }
+            "conic-gradient" => {
+                let (from, at) = match first.split_once("at") {
+                    Some((from, at)) => (from.trim(), Some(at)),
+                    None => (first, None),
+                };
+                let angle = from
+                    .strip_prefix("from")
+                    .and_then(|a| parse_angle(a.trim()));
+                let center = at.map(parse_center);
+                if angle.is_some() || center.is_some() {
+                    Gradient::new(GradientKind::Conic {
+                        angle: angle.unwrap_or(0.0),
+                        center: center.unwrap_or(Vec2::splat(0.5)),
+                    })
+                } else {
+                    Gradient::conic(0.0)
+                }
+            }
+            _ => {
+                tracing::warn!("无效的背景: {:?}", s);
+                return Self::default();
+            }
+        };
+        // 第一项 是形状参数时 不是色标
+        let is_shape = match gradient.kind {
+            GradientKind::Linear { .. } => parse_direction(first).is_some(),
+            _ => first.starts_with("at") || first.starts_with("from"),
+        };
+        if is_shape {
+            args.remove(0);
+        }
+
+        let stops = args
+            .iter()
+            .map(|stop| match stop.rsplit_once(' ') {
+                Some((color, offset)) if offset.ends_with('%') => {
+                    let offset = offset.trim_end_matches('%').parse::<f32>().ok();
+                    (offset.map(|n| n / 100.0), Color::from(color.trim()))
+                }
+                _ => (None, Color::from(*stop)),
+            })
+            .collect::<Vec<_>>();
+        // 省略的位置: 首尾 为 0 和 1, 中间的 在前后 已知位置之间 均匀分布
+        let mut offsets = stops.iter().map(|stop| stop.0).collect::<Vec<_>>();
+        if let Some(first) = offsets.first_mut() {
+            first.get_or_insert(0.0);
+        }
+        if let Some(last) = offsets.last_mut() {
+            last.get_or_insert(1.0);
+        }
+        let mut start = 0;
+        for end in 1..offsets.len() {
+            if let (Some(from), Some(to)) = (offsets[start], offsets[end]) {
+                let count = (end - start) as f32;
+                for (i, offset) in offsets.iter_mut().enumerate().take(end).skip(start + 1) {
+                    *offset = Some(from + (to - from) * (i - start) as f32 / count);
+                }
+                start = end;
+            }
+        }
+        for (offset, (_, color)) in offsets.into_iter().zip(stops) {
+            gradient = gradient.stop(offset.unwrap_or(0.0), color);
+        }
+        Self::Gradient(gradient)
+    }
+}
+
+/// 角度 "90deg", "0.25turn", "1.57rad", 或 方向 "to right", "to top left"; 返回 度数
+fn parse_direction(s: &str) -> Option<f32> {
+    let side = match s.strip_prefix("to") {
+        Some(side) => side.trim(),
+        None => return parse_angle(s),
+    };
+    let (mut x, mut y) = (0.0, 0.0);
+    for word in side.split_whitespace() {
+        match word {
+            "top" => y = -1.0,
+            "bottom" => y = 1.0,
+            "left" => x = -1.0,
+            "right" => x = 1.0,
+            _ => return None,
+        }
+    }
+    let angle = f32::atan2(x, -y).to_degrees();
+    Some(if angle < 0.0 { angle + 360.0 } else { angle })
+}
+
+fn parse_angle(s: &str) -> Option<f32> {
+    if let Some(n) = s.strip_suffix("deg") {
+        return n.trim().parse().ok();
+    }
+    if let Some(n) = s.strip_suffix("turn") {
+        return n.trim().parse::<f32>().ok().map(|n| n * 360.0);
+    }
+    if let Some(n) = s.strip_suffix("rad") {
+        return n.trim().parse::<f32>().ok().map(f32::to_degrees);
+    }
+    None
+}
+
+/// 中心位置 "50% 25%", 相对于矩形; 只有一项时 垂直方向 居中
+fn parse_center(s: &str) -> Vec2 {
+    let mut center = Vec2::splat(0.5);
+    for (i, n) in s.split_whitespace().take(2).enumerate() {
+        if let Ok(n) = n.trim_end_matches('%').parse::<f32>() {
+            center[i] = n / 100.0;
+        }
+    }
+    center
+}
+
+#[test]
+fn test_inherited_style() {
+    use crate::element::Element;
+    use crate::layout::{assert_box, LayoutEngine};
+
+    // 没有加载字体时 英文字符 宽 0.5em
+    let (render_tree, _, _) = Element::new("div")
+        .child(
+            Element::new("section")
+                .font_size(20.0)
+                .line_height(1.5)
+                .color(Color::RED)
+                .children(vec![
+                    Element::new("p").child(Element::text("hello")),
+                    Element::text("hello").font_size(10.0),
+                ]),
+        )
+        .build();
+    let mut engine = LayoutEngine::new();
+    let boxes = |engine: &mut LayoutEngine| {
+        engine.compute(&render_tree, vec2(400.0, 300.0));
+        render_tree
+            .descendants()
+            .map(|node| node.borrow().dom().ava_box())
+            .collect::<Vec<_>>()
+    };
+
+    let b = boxes(&mut engine);
+    // 孙级 继承 字号和行高
+    assert_box(b[3], (0.0, 0.0), (50.0, 30.0));
+    // 覆盖字号, 继承行高
+    assert_box(b[4], (0.0, 30.0), (25.0, 45.0));
+    let text = render_tree.descendants().nth(3).unwrap();
+    assert_eq!(text.borrow().dom().inherited().color, Color::RED);
+    assert_eq!(text.borrow().dom().style().color, None);
+
+    // 修改容器的字号, 继承它的文本 重新排版
+    let mut section = render_tree.first_child().unwrap();
+    section.borrow_mut().dom_mut().set_font_size(10.0);
+    let b = boxes(&mut engine);
+    assert_box(b[3], (0.0, 0.0), (25.0, 15.0));
+    assert_box(b[4], (0.0, 15.0), (25.0, 30.0));
+}
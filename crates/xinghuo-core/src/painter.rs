@@ -1,16 +1,52 @@
-use xinghuo_geom::{color::Color, glam::Vec2, Quad};
+use std::rc::Rc;
+
+use xinghuo_geom::{color::Color, Box2, Gradient, Quad, Rect, Shadow};
+
+use crate::text::{FontSystem, Glyph, TextMeasurer};
 
 pub trait Painter {
     fn rect(&mut self, rect: &[Quad]);
-    fn text(&mut self, text: String, pos: Vec2, size: f32, color: Color);
+    /// 绘制 模糊阴影, 和 Shadow::alpha 的计算结果 一致;
+    /// 目前 只有 wgpu 的实现 真正绘制阴影, 没有 CPU 光栅化的实现, Shadow::alpha 是 它的参考计算
+    fn shadow(&mut self, shadows: &[Shadow]);
+    /// 用渐变 填充 圆角矩形, 和 Gradient::color_at 的计算结果 一致
+    fn gradient(&mut self, rect: Rect, border_radius: f32, gradient: &Gradient);
+    /// 绘制 排版后的字形, 位置是 屏幕坐标系中的 基线起点
+    fn text(&mut self, glyphs: &[Glyph], color: Color);
+    /// 设置裁剪区域, 之后的绘制 只在区域内可见, None 表示不裁剪
+    fn clip(&mut self, rect: Option<Box2>);
     fn resize(&mut self, size: [f32; 2]);
+    /// 提交 一帧中 收集的绘制
     fn render(&mut self);
     fn size(&self) -> [f32; 2];
+    /// 窗口的缩放比例, 物理像素 / 逻辑像素
+    fn scale_factor(&self) -> f32;
+    fn rescale(&mut self, scale_factor: f32);
+    /// 布局时 计算文本尺寸的 文本度量, 和 绘制字形时 使用相同的字体
+    fn text_measurer(&self) -> Rc<dyn TextMeasurer>;
 }
 
-#[derive(Default)]
+/// 不绘制, 只输出 绘制命令; 用于 没有窗口的环境, 阴影 和 渐变 也不光栅化
 pub struct DummyPainter {
     size: [f32; 2],
+    fonts: Rc<FontSystem>,
+}
+
+impl Default for DummyPainter {
+    fn default() -> Self {
+        Self {
+            size: [0.0; 2],
+            fonts: FontSystem::shared(),
+        }
+    }
+}
+
+impl DummyPainter {
+    /// 使用 指定的字体集合 计算文本尺寸
+    pub fn with_fonts(mut self, fonts: Rc<FontSystem>) -> Self {
+        self.fonts = fonts;
+        self
+    }
 }
 
 impl Painter for DummyPainter {
@@ -18,13 +54,29 @@ impl Painter for DummyPainter {
         println!("-- draw rect --> rect: {:?}", rect);
     }
 
-    fn text(&mut self, text: String, pos: Vec2, size: f32, color: Color) {
+    fn shadow(&mut self, shadows: &[Shadow]) {
+        println!("-- draw shadow --> shadows: {:?}", shadows);
+    }
+
+    fn gradient(&mut self, rect: Rect, border_radius: f32, gradient: &Gradient) {
         println!(
-            "-- draw text --> text: {:?} pos: {:?} size: {:?} color: {:?}",
-            &text, &pos, &size, &color
+            "-- draw gradient --> rect: {:?} border_radius: {:?} gradient: {:?}",
+            rect, border_radius, gradient
         );
     }
 
+    fn text(&mut self, glyphs: &[Glyph], color: Color) {
+        println!(
+            "-- draw text --> glyphs: {:?} color: {:?}",
+            glyphs.len(),
+            color
+        );
+    }
+
+    fn clip(&mut self, rect: Option<Box2>) {
+        println!("-- clip --> rect: {:?}", rect);
+    }
+
     fn resize(&mut self, size: [f32; 2]) {
         println!("resize event: size [{:?}]", &size);
     }
@@ -34,4 +86,182 @@ impl Painter for DummyPainter {
     fn size(&self) -> [f32; 2] {
         self.size
     }
+
+    fn scale_factor(&self) -> f32 {
+        1.0
+    }
+
+    fn rescale(&mut self, scale_factor: f32) {
+        println!("rescale event: scale_factor [{:?}]", &scale_factor);
+    }
+
+    fn text_measurer(&self) -> Rc<dyn TextMeasurer> {
+        self.fonts.clone()
+    }
+}
+
+#[test]
+fn test_box_shadow() {
+    use std::cell::RefCell;
+
+    use crate::app::AppBuilder;
+    use crate::element::Element;
+    use crate::BoxShadow;
+    use xinghuo_geom::vec2;
+
+    let shadows = BoxShadow::parse_list("0 4 12 #00000040, inset 1 2 (255, 0, 0, 255)");
+    assert_eq!(shadows.len(), 2);
+    assert_eq!(shadows[0].offset, vec2(0.0, 4.0));
+    assert_eq!(shadows[0].blur, 12.0);
+    assert_eq!(shadows[0].color, Color::from("#00000040"));
+    assert!(shadows[1].inset);
+    assert_eq!(shadows[1].color, Color::RED);
+    assert_eq!(shadows[1].offset, vec2(1.0, 2.0));
+    assert_eq!(shadows[1].blur, 0.0);
+    assert!(BoxShadow::parse_list("none").is_empty());
+
+    #[derive(Debug)]
+    enum Command {
+        Rect(Vec<Quad>),
+        Shadow(Vec<Shadow>),
+        Gradient,
+    }
+
+    /// 记录 绘制命令的顺序
+    #[derive(Default)]
+    struct RecordingPainter(Rc<RefCell<Vec<Command>>>);
+
+    impl Painter for RecordingPainter {
+        fn rect(&mut self, rect: &[Quad]) {
+            self.0.borrow_mut().push(Command::Rect(rect.to_vec()));
+        }
+        fn shadow(&mut self, shadows: &[Shadow]) {
+            self.0.borrow_mut().push(Command::Shadow(shadows.to_vec()));
+        }
+        fn gradient(&mut self, _rect: Rect, _border_radius: f32, _gradient: &Gradient) {
+            self.0.borrow_mut().push(Command::Gradient);
+        }
+        fn text(&mut self, _glyphs: &[Glyph], _color: Color) {}
+        fn clip(&mut self, _rect: Option<Box2>) {}
+        fn resize(&mut self, _size: [f32; 2]) {}
+        fn render(&mut self) {}
+        fn size(&self) -> [f32; 2] {
+            [200.0, 200.0]
+        }
+        fn scale_factor(&self) -> f32 {
+            1.0
+        }
+        fn rescale(&mut self, _scale_factor: f32) {}
+        fn text_measurer(&self) -> Rc<dyn TextMeasurer> {
+            FontSystem::shared()
+        }
+    }
+
+    let painter = RecordingPainter::default();
+    let commands = painter.0.clone();
+    let mut app = AppBuilder::new(Element::new("div").child(
+        Element::new("card").width(100.0).height(50.0).inline_style(
+            "box-shadow: 0 2 8 #000000, 0 0 0 2 #ff0000, inset 0 1 2 #ffffff; border-radius: 10; border-width: 1",
+        ),
+    ))
+    .with_draw(painter)
+    .build();
+    app.render();
+
+    // 外阴影 在背景之前, 逆序绘制; 内阴影 在背景之后
+    let commands = commands.borrow();
+    let card = commands
+        .iter()
+        .position(|command| matches!(command, Command::Shadow(shadows) if shadows.len() == 2))
+        .unwrap();
+    let (outer, background, inset) = match &commands[card..card + 3] {
+        [Command::Shadow(outer), Command::Rect(background), Command::Shadow(inset)] => {
+            (outer, background[0], inset)
+        }
+        commands => panic!("绘制顺序错误: {:?}", commands),
+    };
+    // 背景 和 阴影 的形状相同
+    assert_eq!(background.rect.to_box2d(), outer[1].rect.to_box2d());
+    assert_eq!(background.border_radius, 10.0);
+    assert_eq!(background.border_width, 1.0);
+    assert_eq!(outer[0].spread, 2.0);
+    assert_eq!(outer[1].blur, 8.0);
+    assert_eq!(outer[1].border_radius, 10.0);
+    assert!(inset[0].is_inset());
+
+    // CPU 计算的 不透明度: 外阴影 只在盒子外, 向外逐渐变淡
+    let shadow = outer[1];
+    let rect = shadow.rect;
+    let below = |d: f32| shadow.alpha(vec2(rect.center().x, rect.max_y() + d));
+    assert_eq!(shadow.alpha(rect.center()), 0.0);
+    assert!(below(1.0) > below(4.0) && below(4.0) > below(8.0));
+    assert!(below(2.0 + 3.0 * 4.0) < 0.01);
+    assert!(shadow
+        .bounds()
+        .contains(vec2(rect.center().x, rect.max_y() + 13.0)));
+    // 没有模糊的 扩展阴影 是实心的边
+    let ring = outer[0];
+    assert!(ring.alpha(vec2(rect.min_x() - 1.0, rect.center().y)) > 0.95);
+    assert!(ring.alpha(vec2(rect.min_x() - 4.0, rect.center().y)) < 0.01);
+    // 内阴影 只在盒子内, 上边 偏移后 最深
+    let inset = inset[0];
+    assert_eq!(inset.alpha(vec2(rect.center().x, rect.min_y() - 1.0)), 0.0);
+    assert!(inset.alpha(vec2(rect.center().x, rect.min_y() + 0.5)) > 0.5);
+    assert!(inset.alpha(rect.center()) < 0.01);
+}
+
+#[test]
+fn test_gradient() {
+    use crate::Background;
+    use xinghuo_geom::{vec2, GradientKind};
+
+    let background = Background::from("linear-gradient(to right, #ff0000, #00ff00, #0000ff 80%)");
+    let gradient = match background {
+        Background::Gradient(gradient) => gradient,
+        _ => panic!("应该是渐变"),
+    };
+    assert_eq!(gradient.kind, GradientKind::Linear { angle: 90.0 });
+    // 省略的位置 在相邻位置之间 均匀分布
+    let offsets = gradient.stops.iter().map(|stop| stop.0).collect::<Vec<_>>();
+    assert_eq!(offsets, vec![0.0, 0.4, 0.8]);
+    assert_eq!(gradient.stops[1].1, Color::from("#00ff00"));
+
+    let rect = Rect::new(vec2(10.0, 10.0), vec2(100.0, 20.0));
+    assert_eq!(gradient.color_at(rect, vec2(10.0, 20.0)), Color::RED);
+    assert_eq!(gradient.color_at(rect, vec2(50.0, 0.0)), Color::GLEEN);
+    assert_eq!(
+        gradient.color_at(rect, vec2(20.0, 20.0)),
+        Color::RED.lerp(&Color::GLEEN, 0.25)
+    );
+    // 最后一个色标之后 取它的颜色
+    assert_eq!(gradient.color_at(rect, vec2(100.0, 20.0)), Color::BLUE);
+
+    let radial = Background::from("radial-gradient(at 0% 50%, #ffffff, #000000)");
+    assert_eq!(
+        radial,
+        Background::Gradient(
+            Gradient::new(GradientKind::Radial {
+                center: vec2(0.0, 0.5)
+            })
+            .stop(0.0, "#ffffff")
+            .stop(1.0, "#000000")
+        )
+    );
+    let conic = match Background::from("conic-gradient(from 90deg, #ff0000, #0000ff)") {
+        Background::Gradient(gradient) => gradient,
+        _ => panic!("应该是渐变"),
+    };
+    // 从右侧 开始 顺时针
+    let square = Rect::new(vec2(0.0, 0.0), vec2(10.0, 10.0));
+    assert!(
+        conic
+            .progress(square, vec2(10.0, 5.0))
+            .min(1.0 - conic.progress(square, vec2(10.0, 5.0)))
+            < 1e-5
+    );
+    assert!((conic.progress(square, vec2(5.0, 10.0)) - 0.25).abs() < 1e-5);
+    assert_eq!(
+        Background::from("#123456"),
+        Background::Color(Color::from("#123456"))
+    );
 }
@@ -0,0 +1,814 @@
+//! 样式表: 选择器匹配 渲染树上的节点, 按 优先级 和 出现顺序 层叠声明
+//!
+//! ```text
+//! /* 注释 */
+//! div { padding: 4; }
+//! #main > .card, .list .item { width: 50%; background-color: #ff0000; }
+//! ```
+//! 支持的选择器: 标签名, `*`, `#id`, `.class`, `:hover` 等伪类 组成的复合选择器,
+//! 以及 后代 (空白) 和 子级 (`>`) 组合符
+
+use crate::element::RenderObject;
+use crate::error::{Error, Result};
+use crate::prelude::*;
+use crate::theme::Theme;
+use crate::*;
+
+/// 选择器的优先级: (id 数量, 类名和伪类 数量, 标签名 数量), 按字典序比较
+pub type Specificity = (u32, u32, u32);
+
+/// 复合选择器, 如: "div#main.card"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Compound {
+    /// None 表示 任意标签
+    pub tag: Option<String>,
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub pseudo_classes: Vec<PseudoClass>,
+}
+
+impl Compound {
+    fn matches(&self, dom: &DomElement) -> bool {
+        self.tag.iter().all(|tag| tag == dom.tag())
+            && self.id.iter().all(|id| id == dom.id())
+            && self.classes.iter().all(|class| dom.has_class(class))
+            && self
+                .pseudo_classes
+                .iter()
+                .all(|class| dom.state().contains(*class))
+    }
+}
+
+/// 复合选择器 和 左侧复合选择器 的关系
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combinator {
+    /// "a b": b 是 a 的后代
+    Descendant,
+    /// "a > b": b 是 a 的直接子级
+    Child,
+}
+
+/// 由组合符 连接的 复合选择器, 第一项的组合符 没有意义
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub parts: Vec<(Combinator, Compound)>,
+}
+
+impl Selector {
+    pub fn parse(s: &str) -> Result<Self> {
+        let error = || Error::StyleSheetError(format!("无效的选择器: {:?}", s));
+        let mut parts = Vec::new();
+        let mut combinator = Combinator::Descendant;
+        let mut chars = s.trim().chars().peekable();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            if c == '>' {
+                // 组合符 前面必须有 复合选择器, 不能连续出现
+                if parts.is_empty() || combinator == Combinator::Child {
+                    return Err(error());
+                }
+                combinator = Combinator::Child;
+                chars.next();
+                continue;
+            }
+
+            let mut compound = Compound::default();
+            let mut first = true;
+            while let Some(&c) = chars.peek() {
+                match c {
+                    '*' if first => {
+                        chars.next();
+                    }
+                    ':' => {
+                        chars.next();
+                        let class =
+                            PseudoClass::from_name(&take_ident(&mut chars)).ok_or_else(error)?;
+                        compound.pseudo_classes.push(class);
+                    }
+                    '#' | '.' => {
+                        chars.next();
+                        let name = take_ident(&mut chars);
+                        if name.is_empty() {
+                            return Err(error());
+                        }
+                        if c == '#' {
+                            compound.id = Some(name);
+                        } else {
+                            compound.classes.push(name);
+                        }
+                    }
+                    c if first && is_ident_char(c) => {
+                        compound.tag = Some(take_ident(&mut chars));
+                    }
+                    c if c.is_whitespace() || c == '>' => break,
+                    _ => return Err(error()),
+                }
+                first = false;
+            }
+            parts.push((combinator, compound));
+            combinator = Combinator::Descendant;
+        }
+        if parts.is_empty() || combinator == Combinator::Child {
+            return Err(error());
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn specificity(&self) -> Specificity {
+        self.parts
+            .iter()
+            .fold((0, 0, 0), |(ids, classes, tags), (_, compound)| {
+                (
+                    ids + compound.id.is_some() as u32,
+                    classes + (compound.classes.len() + compound.pseudo_classes.len()) as u32,
+                    tags + compound.tag.is_some() as u32,
+                )
+            })
+    }
+
+    /// 从右向左 匹配, 最右侧的复合选择器 匹配 node 本身
+    pub fn matches(&self, node: &TreeNode<Box<dyn RenderObject>>) -> bool {
+        self.matches_at(self.parts.len() - 1, node)
+    }
+
+    fn matches_at(&self, index: usize, node: &TreeNode<Box<dyn RenderObject>>) -> bool {
+        let (combinator, compound) = &self.parts[index];
+        if !compound.matches(node.borrow().dom()) {
+            return false;
+        }
+        if index == 0 {
+            return true;
+        }
+        match combinator {
+            Combinator::Child => {
+                matches!(node.parent(), Some(parent) if self.matches_at(index - 1, &parent))
+            }
+            Combinator::Descendant => node
+                .ancestors()
+                .skip(1)
+                .any(|ancestor| self.matches_at(index - 1, &ancestor)),
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+fn take_ident(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut ident = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_ident_char(c) {
+            break;
+        }
+        ident.push(c);
+        chars.next();
+    }
+    ident
+}
+
+/// 样式表支持的属性, 名称 和 DomElement 的 set_* 方法 对应 (kebab-case)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Property {
+    Display,
+    Width,
+    Height,
+    MinWidth,
+    MaxWidth,
+    MinHeight,
+    MaxHeight,
+    BoxSizing,
+    AspectRatio,
+    Padding,
+    Margin,
+    BorderWidth,
+    BorderRadius,
+    BorderColor,
+    Background,
+    BackgroundColor,
+    BoxShadow,
+    FlexGrow,
+    FlexShrink,
+    FlexBasis,
+    FlexWrap,
+    JustifyContent,
+    AlignItems,
+    AlignSelf,
+    Gap,
+    GridTemplateColumns,
+    GridTemplateRows,
+    GridColumn,
+    GridRow,
+    VerticalAlign,
+    TextAlign,
+    FontSize,
+    LineHeight,
+    /// 应用 当前主题的 字体令牌
+    Typography,
+    FontFamily,
+    FontWeight,
+    FontStyle,
+    Color,
+    StackAlign,
+    Anchor,
+    Stretch,
+    Position,
+    Top,
+    Right,
+    Bottom,
+    Left,
+    Overflow,
+}
+
+const PROPERTIES: &[(&str, Property)] = &[
+    ("display", Property::Display),
+    ("width", Property::Width),
+    ("height", Property::Height),
+    ("min-width", Property::MinWidth),
+    ("max-width", Property::MaxWidth),
+    ("min-height", Property::MinHeight),
+    ("max-height", Property::MaxHeight),
+    ("box-sizing", Property::BoxSizing),
+    ("aspect-ratio", Property::AspectRatio),
+    ("padding", Property::Padding),
+    ("margin", Property::Margin),
+    ("border-width", Property::BorderWidth),
+    ("border-radius", Property::BorderRadius),
+    ("border-color", Property::BorderColor),
+    ("background", Property::Background),
+    ("background-color", Property::BackgroundColor),
+    ("box-shadow", Property::BoxShadow),
+    ("flex-grow", Property::FlexGrow),
+    ("flex-shrink", Property::FlexShrink),
+    ("flex-basis", Property::FlexBasis),
+    ("flex-wrap", Property::FlexWrap),
+    ("justify-content", Property::JustifyContent),
+    ("align-items", Property::AlignItems),
+    ("align-self", Property::AlignSelf),
+    ("gap", Property::Gap),
+    ("grid-template-columns", Property::GridTemplateColumns),
+    ("grid-template-rows", Property::GridTemplateRows),
+    ("grid-column", Property::GridColumn),
+    ("grid-row", Property::GridRow),
+    ("vertical-align", Property::VerticalAlign),
+    ("text-align", Property::TextAlign),
+    ("font-size", Property::FontSize),
+    ("line-height", Property::LineHeight),
+    ("typography", Property::Typography),
+    ("font-family", Property::FontFamily),
+    ("font-weight", Property::FontWeight),
+    ("font-style", Property::FontStyle),
+    ("color", Property::Color),
+    ("stack-align", Property::StackAlign),
+    ("anchor", Property::Anchor),
+    ("stretch", Property::Stretch),
+    ("position", Property::Position),
+    ("top", Property::Top),
+    ("right", Property::Right),
+    ("bottom", Property::Bottom),
+    ("left", Property::Left),
+    ("overflow", Property::Overflow),
+];
+
+impl Property {
+    pub fn from_name(name: &str) -> Option<Self> {
+        PROPERTIES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, property)| *property)
+    }
+
+    pub fn name(&self) -> &'static str {
+        PROPERTIES
+            .iter()
+            .find(|(_, p)| p == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default()
+    }
+}
+
+/// 一条声明, 值 在应用时 才解析
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub property: Property,
+    pub value: String,
+}
+
+impl Declaration {
+    /// 把声明 应用到节点上, 先替换 theme 的令牌; 无法解析的值 忽略
+    pub fn apply(&self, dom: &mut DomElement, theme: &Theme) {
+        let value = match theme.resolve(&self.value) {
+            Some(value) => value,
+            None => return self.warn(),
+        };
+        let value = value.trim();
+        match self.property {
+            Property::Display => {
+                if let Some(v) = self.keyword(value, LAYOUTS) {
+                    dom.set_layout(v)
+                }
+            }
+            Property::Width => dom.set_width(value),
+            Property::Height => dom.set_height(value),
+            Property::MinWidth => dom.set_min_width(value),
+            Property::MaxWidth => dom.set_max_width(value),
+            Property::MinHeight => dom.set_min_height(value),
+            Property::MaxHeight => dom.set_max_height(value),
+            Property::BoxSizing => {
+                if let Some(v) = self.keyword(value, BOX_SIZINGS) {
+                    dom.set_box_sizing(v)
+                }
+            }
+            Property::AspectRatio => {
+                if let Some(v) = self.number(value) {
+                    dom.set_aspect_ratio(v)
+                }
+            }
+            Property::Padding => dom.set_padding(value),
+            Property::Margin => dom.set_margin(value),
+            Property::BorderWidth => dom.set_border_width(value),
+            Property::BorderRadius => dom.set_border_radius(value),
+            Property::BorderColor => dom.set_border_color(value),
+            Property::Background => dom.set_background(value),
+            Property::BackgroundColor => dom.set_background_color(value),
+            Property::BoxShadow => dom.set_box_shadow(BoxShadow::parse_list(value)),
+            Property::FlexGrow => {
+                if let Some(v) = self.number(value) {
+                    dom.set_flex_grow(v)
+                }
+            }
+            Property::FlexShrink => {
+                if let Some(v) = self.number(value) {
+                    dom.set_flex_shrink(v)
+                }
+            }
+            Property::FlexBasis => dom.set_flex_basis(value),
+            Property::FlexWrap => {
+                if let Some(v) = self.keyword(value, FLEX_WRAPS) {
+                    dom.set_flex_wrap(v)
+                }
+            }
+            Property::JustifyContent => {
+                if let Some(v) = self.keyword(value, JUSTIFY_CONTENTS) {
+                    dom.set_justify_content(v)
+                }
+            }
+            Property::AlignItems => {
+                if let Some(v) = self.keyword(value, ALIGN_ITEMS) {
+                    dom.set_align_items(v)
+                }
+            }
+            Property::AlignSelf => {
+                if let Some(v) = self.keyword(value, ALIGN_ITEMS) {
+                    dom.set_align_self(v)
+                }
+            }
+            Property::Gap => dom.set_gap(value),
+            Property::GridTemplateColumns => dom.set_grid_template_columns(value),
+            Property::GridTemplateRows => dom.set_grid_template_rows(value),
+            Property::GridColumn => dom.set_grid_column(value),
+            Property::GridRow => dom.set_grid_row(value),
+            Property::VerticalAlign => {
+                if let Some(v) = self.keyword(value, VERTICAL_ALIGNS) {
+                    dom.set_vertical_align(v)
+                }
+            }
+            Property::TextAlign => {
+                if let Some(v) = self.keyword(value, TEXT_ALIGNS) {
+                    dom.set_text_align(v)
+                }
+            }
+            Property::FontSize => {
+                if let Some(v) = self.number(value) {
+                    dom.set_font_size(v)
+                }
+            }
+            Property::LineHeight => {
+                if let Some(v) = self.number(value) {
+                    dom.set_line_height(v)
+                }
+            }
+            Property::Typography => match theme.typography(value) {
+                Some(typography) => {
+                    dom.set_font_size(typography.font_size);
+                    dom.set_line_height(typography.line_height);
+                    dom.set_font_weight(typography.font_weight);
+                    if let Some(font_family) = &typography.font_family {
+                        dom.set_font_family(font_family);
+                    }
+                }
+                None => self.warn(),
+            },
+            Property::FontFamily => {
+                dom.set_font_family(value.trim_matches(|c| c == '"' || c == '\''))
+            }
+            Property::FontWeight => dom.set_font_weight(value),
+            Property::FontStyle => dom.set_font_style(value),
+            Property::Color => dom.set_color(value),
+            Property::StackAlign => {
+                if let Some(v) = self.keyword(value, ANCHORS) {
+                    dom.set_stack_align(v)
+                }
+            }
+            Property::Anchor => {
+                if let Some(v) = self.keyword(value, ANCHORS) {
+                    dom.set_anchor(v)
+                }
+            }
+            Property::Stretch => {
+                if let Some(v) = self.keyword(value, &[("true", true), ("false", false)]) {
+                    dom.set_stretch(v)
+                }
+            }
+            Property::Position => {
+                if let Some(v) = self.keyword(value, POSITIONS) {
+                    dom.set_position(v)
+                }
+            }
+            Property::Top => dom.set_top(value),
+            Property::Right => dom.set_right(value),
+            Property::Bottom => dom.set_bottom(value),
+            Property::Left => dom.set_left(value),
+            Property::Overflow => {
+                if let Some(v) = self.keyword(value, OVERFLOWS) {
+                    dom.set_overflow(v)
+                }
+            }
+        }
+    }
+
+    fn number(&self, value: &str) -> Option<f32> {
+        let number = value.parse::<f32>().ok();
+        if number.is_none() {
+            self.warn();
+        }
+        number
+    }
+
+    fn keyword<T: Copy>(&self, value: &str, keywords: &[(&str, T)]) -> Option<T> {
+        let keyword = keywords.iter().find(|(k, _)| *k == value).map(|(_, v)| *v);
+        if keyword.is_none() {
+            self.warn();
+        }
+        keyword
+    }
+
+    fn warn(&self) {
+        tracing::warn!("样式表: 无法解析 {}: {}", self.property.name(), self.value);
+    }
+}
+
+/* 枚举属性的 关键字 */
+const LAYOUTS: &[(&str, Layout)] = &[
+    ("inline", Layout::Inline),
+    ("inline-block", Layout::InlineBlock),
+    ("block", Layout::Block),
+    ("flex", Layout::RowFlex),
+    ("row-flex", Layout::RowFlex),
+    ("col-flex", Layout::ColFlex),
+    ("grid", Layout::Grid),
+    ("stack", Layout::Stack),
+];
+const BOX_SIZINGS: &[(&str, BoxSizing)] = &[
+    ("content-box", BoxSizing::ContentBox),
+    ("border-box", BoxSizing::BorderBox),
+];
+const FLEX_WRAPS: &[(&str, FlexWrap)] = &[
+    ("nowrap", FlexWrap::NoWrap),
+    ("wrap", FlexWrap::Wrap),
+    ("wrap-reverse", FlexWrap::WrapReverse),
+];
+const JUSTIFY_CONTENTS: &[(&str, JustifyContent)] = &[
+    ("flex-start", JustifyContent::FlexStart),
+    ("flex-end", JustifyContent::FlexEnd),
+    ("center", JustifyContent::Center),
+    ("space-between", JustifyContent::SpaceBetween),
+    ("space-around", JustifyContent::SpaceAround),
+    ("space-evenly", JustifyContent::SpaceEvenly),
+];
+const ALIGN_ITEMS: &[(&str, AlignItems)] = &[
+    ("flex-start", AlignItems::FlexStart),
+    ("flex-end", AlignItems::FlexEnd),
+    ("center", AlignItems::Center),
+    ("stretch", AlignItems::Stretch),
+];
+const VERTICAL_ALIGNS: &[(&str, VerticalAlign)] = &[
+    ("baseline", VerticalAlign::Baseline),
+    ("top", VerticalAlign::Top),
+    ("middle", VerticalAlign::Middle),
+    ("bottom", VerticalAlign::Bottom),
+];
+const TEXT_ALIGNS: &[(&str, TextAlign)] = &[
+    ("left", TextAlign::Left),
+    ("center", TextAlign::Center),
+    ("right", TextAlign::Right),
+];
+const ANCHORS: &[(&str, Anchor)] = &[
+    ("top-left", Anchor::TopLeft),
+    ("top", Anchor::Top),
+    ("top-right", Anchor::TopRight),
+    ("left", Anchor::Left),
+    ("center", Anchor::Center),
+    ("right", Anchor::Right),
+    ("bottom-left", Anchor::BottomLeft),
+    ("bottom", Anchor::Bottom),
+    ("bottom-right", Anchor::BottomRight),
+];
+const POSITIONS: &[(&str, Position)] = &[
+    ("static", Position::Static),
+    ("relative", Position::Relative),
+    ("absolute", Position::Absolute),
+    ("fixed", Position::Fixed),
+];
+const OVERFLOWS: &[(&str, Overflow)] = &[
+    ("visible", Overflow::Visible),
+    ("hidden", Overflow::Hidden),
+    ("scroll", Overflow::Scroll),
+    ("auto", Overflow::Auto),
+];
+
+/// 一条规则: 逗号分隔的 选择器列表 和 声明块
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub selectors: Vec<Selector>,
+    pub declarations: Vec<Declaration>,
+}
+
+impl Rule {
+    /// 匹配 node 的选择器中 最高的优先级, 不匹配时 返回 None
+    pub fn matches(&self, node: &TreeNode<Box<dyn RenderObject>>) -> Option<Specificity> {
+        self.selectors
+            .iter()
+            .filter(|selector| selector.matches(node))
+            .map(Selector::specificity)
+            .max()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyleSheet {
+    pub rules: Vec<Rule>,
+}
+
+impl StyleSheet {
+    /// 解析样式表, 未知的属性 忽略并警告, 选择器 或 括号 无效时 返回错误
+    pub fn parse(source: &str) -> Result<Self> {
+        let source = strip_comments(source)?;
+        let mut rules = Vec::new();
+        let mut rest = source.as_str();
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..]
+                .find('}')
+                .map(|close| open + close)
+                .ok_or_else(|| Error::StyleSheetError("缺少 '}'".to_string()))?;
+            let selectors = rest[..open]
+                .split(',')
+                .map(Selector::parse)
+                .collect::<Result<Vec<_>>>()?;
+            let block = &rest[open + 1..close];
+            if block.contains('{') {
+                return Err(Error::StyleSheetError("不支持 嵌套的 '{'".to_string()));
+            }
+            let declarations = parse_declarations(block);
+            rules.push(Rule {
+                selectors,
+                declarations,
+            });
+            rest = &rest[close + 1..];
+        }
+        if !rest.trim().is_empty() {
+            return Err(Error::StyleSheetError(format!(
+                "无法解析: {:?}",
+                rest.trim()
+            )));
+        }
+        Ok(Self { rules })
+    }
+
+    /// 匹配 node 的声明, 按 优先级 和 出现顺序 排列, 后面的 覆盖前面的
+    pub fn declarations(&self, node: &TreeNode<Box<dyn RenderObject>>) -> Vec<&Declaration> {
+        let mut matched = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter_map(|(order, rule)| rule.matches(node).map(|spec| (spec, order, rule)))
+            .collect::<Vec<_>>();
+        matched.sort_by_key(|(spec, order, _)| (*spec, *order));
+        matched
+            .into_iter()
+            .flat_map(|(_, _, rule)| &rule.declarations)
+            .collect()
+    }
+
+    /// 使用 theme 层叠 整棵渲染树, 样式改变的节点 标记为 dirty
+    pub fn apply(&self, root: &TreeNode<Box<dyn RenderObject>>, theme: &Theme) {
+        cascade(Some(self), theme, root);
+    }
+}
+
+/// 层叠 node 和 它的后代: 依次应用 样式表中匹配的声明, 内联声明 和 节点当前状态的 状态样式声明,
+/// 声明中的令牌 由 theme 替换; 根节点的样式 由布局引擎 根据窗口尺寸设置, 不参与层叠
+pub fn cascade(
+    stylesheet: Option<&StyleSheet>,
+    theme: &Theme,
+    node: &TreeNode<Box<dyn RenderObject>>,
+) {
+    for mut node in node.descendants() {
+        if node.parent().is_none() {
+            continue;
+        }
+        let declarations = stylesheet
+            .map(|stylesheet| stylesheet.declarations(&node))
+            .unwrap_or_default();
+        let own = {
+            let node = node.borrow();
+            let dom = node.dom();
+            dom.inline_style()
+                .iter()
+                .chain(dom.state_declarations())
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        node.borrow_mut().dom_mut().cascade(|dom| {
+            for declaration in declarations.into_iter().chain(&own) {
+                declaration.apply(dom, theme);
+            }
+        });
+    }
+}
+
+/// 解析 分号分隔的声明, 如: "width: 10; color: #ff0000", 未知的属性 和 空的值 忽略并警告
+pub fn parse_declarations(block: &str) -> Vec<Declaration> {
+    block
+        .split(';')
+        .filter_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            let name = name.trim().to_lowercase();
+            if value.trim().is_empty() {
+                tracing::warn!("样式表: 属性 {} 的值 为空", name);
+                return None;
+            }
+            match Property::from_name(&name) {
+                Some(property) => Some(Declaration {
+                    property,
+                    value: value.trim().to_string(),
+                }),
+                None => {
+                    tracing::warn!("样式表: 未知的属性 {}", name);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn strip_comments(source: &str) -> Result<String> {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        let end = rest[start + 2..]
+            .find("*/")
+            .ok_or_else(|| Error::StyleSheetError("注释 缺少 '*/'".to_string()))?;
+        rest = &rest[start + 2 + end + 2..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[test]
+fn test_selector() {
+    let selector = Selector::parse("#main > div.card.active:hover  .item").unwrap();
+    assert_eq!(
+        selector.parts,
+        vec![
+            (
+                Combinator::Descendant,
+                Compound {
+                    id: Some("main".to_string()),
+                    ..Default::default()
+                }
+            ),
+            (
+                Combinator::Child,
+                Compound {
+                    tag: Some("div".to_string()),
+                    classes: vec!["card".to_string(), "active".to_string()],
+                    pseudo_classes: vec![PseudoClass::Hover],
+                    ..Default::default()
+                }
+            ),
+            (
+                Combinator::Descendant,
+                Compound {
+                    classes: vec!["item".to_string()],
+                    ..Default::default()
+                }
+            ),
+        ]
+    );
+    assert_eq!(selector.specificity(), (1, 4, 1));
+    assert_eq!(Selector::parse("*").unwrap().specificity(), (0, 0, 0));
+    assert!(
+        Selector::parse("#a").unwrap().specificity()
+            > Selector::parse("div .a.b").unwrap().specificity()
+    );
+
+    // 组合符 缺少 左侧 或 右侧的 复合选择器, 连续的组合符, 未知的伪类, 空的 类名
+    for s in ["a >", "> a", "a > > b", "a:unknown", "a.", ""] {
+        assert!(Selector::parse(s).is_err(), "{:?}", s);
+    }
+}
+
+#[test]
+fn test_stylesheet_parse() {
+    let sheet = StyleSheet::parse(
+        "
+        /* 注释 { 中的括号 } */
+        .list .item, #first { width: 40; unknown: 1; }
+        div{height:10}
+        ",
+    )
+    .unwrap();
+    assert_eq!(sheet.rules.len(), 2);
+    assert_eq!(sheet.rules[0].selectors.len(), 2);
+    // 未知的属性 被忽略
+    assert_eq!(sheet.rules[0].declarations.len(), 1);
+    assert_eq!(sheet.rules[1].declarations[0].value, "10");
+
+    // 空的值 被忽略, 不影响 其他声明
+    let sheet = StyleSheet::parse(".a { padding: ; border-radius: ; width: 10; }").unwrap();
+    assert_eq!(sheet.rules[0].declarations.len(), 1);
+    let (render_tree, _, _) = crate::element::Element::new("div")
+        .child(crate::element::Element::new("p").class("a"))
+        .build();
+    sheet.apply(&render_tree, &Theme::light());
+    let p = render_tree.first_child().unwrap();
+    assert_eq!(p.borrow().dom().style().width, Size::from(10.0));
+    assert_eq!(p.borrow().dom().style().padding, Quat::default());
+    assert_eq!(Quat::from(" "), Quat::default());
+    // 长度 符合 十六进制颜色, 但包含 多字节字符
+    let sheet = StyleSheet::parse(".a { background-color: #a\u{e9}\u{e9}a; }").unwrap();
+    sheet.apply(&render_tree, &Theme::light());
+    assert_eq!(
+        p.borrow().dom().style().background,
+        Background::Color(Color::default())
+    );
+
+    for source in [
+        "div > { width: 10; }",
+        "> div { width: 10; }",
+        "div { width: 10;",
+        "div { a { width: 10; } }",
+        "div { width: 10; } span",
+        "div { width: 10; } /* 没有结束的注释",
+    ] {
+        assert!(StyleSheet::parse(source).is_err(), "{:?}", source);
+    }
+}
+
+#[test]
+fn test_cascade() {
+    use crate::element::Element;
+    use crate::layout::{assert_box, LayoutEngine};
+
+    let sheet = StyleSheet::parse(
+        "
+        /* 后代 和 子级 */
+        .list .item { height: 10; }
+        .list > .item { width: 40; }
+        /* id 优先于 类名, 与顺序无关 */
+        #first { width: 80; }
+        .item { width: 20; unknown: 1; }
+        /* 优先级相同时 后面的覆盖前面的 */
+        .list > .item { height: 20; }
+        ",
+    )
+    .unwrap();
+
+    let (render_tree, _, _) = Element::new("div")
+        .child(Element::new("ul").class("list").children(vec![
+            Element::new("li").id("first").class("item"),
+            Element::new("li").class("item"),
+            Element::new("div").child(Element::new("li").class("item")),
+        ]))
+        .build();
+    sheet.apply(&render_tree, &Theme::light());
+    let mut engine = LayoutEngine::new();
+    engine.compute(&render_tree, vec2(400.0, 300.0));
+    let b = render_tree
+        .descendants()
+        .map(|node| node.borrow().dom().ava_box())
+        .collect::<Vec<_>>();
+    assert_box(b[2], (0.0, 0.0), (80.0, 20.0));
+    assert_box(b[3], (0.0, 20.0), (40.0, 40.0));
+    // 不是 .list 的直接子级, 只匹配 后代选择器
+    assert_box(b[5], (0.0, 40.0), (20.0, 50.0));
+
+    // 再次层叠 结果相同时 不需要重新布局
+    sheet.apply(&render_tree, &Theme::light());
+    assert!(engine.compute(&render_tree, vec2(400.0, 300.0)).is_empty());
+}
@@ -0,0 +1,1137 @@
+/*
+    文本: 字体管理, 字形排版 和 分行
+    分行规则 使用 Unicode 换行算法 (UAX #14), 中日韩文字 在字符之间 可以换行
+    富文本的 多个片段 (Span) 作为一个段落 一起分行, 换行位置 可以跨越片段边界
+*/
+
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
+use ab_glyph::{Font as _, FontArc, PxScale, ScaleFont as _};
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+
+use crate::error::{Error, Result};
+use crate::prelude::*;
+use crate::TextAlign;
+
+/// 字重, 100 ~ 900, 400 为正常, 700 为粗体
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    pub const NORMAL: Self = Self(400);
+    pub const BOLD: Self = Self(700);
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+impl From<u16> for FontWeight {
+    fn from(weight: u16) -> Self {
+        Self(weight)
+    }
+}
+
+/// "normal", "bold" 或 数字, 如: "600"
+impl From<&str> for FontWeight {
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "bold" => Self::BOLD,
+            s => s.parse().map(Self).unwrap_or_default(),
+        }
+    }
+}
+
+/// 字体样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+}
+
+impl Default for FontStyle {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// "italic" 或 "oblique" 为斜体, 其余为 Normal
+impl From<&str> for FontStyle {
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "italic" | "oblique" => Self::Italic,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// 已加载字体的 索引, 第一个加载的字体 是默认字体
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontId(pub usize);
+
+/// TrueType/OpenType 字体
+#[derive(Clone)]
+pub struct Font {
+    inner: FontArc,
+    /// 字体的 name 表中的 字族名称
+    family: String,
+    weight: FontWeight,
+    style: FontStyle,
+    /// 下划线 和 删除线 的 (位置, 粗细), 相对于 em 的比例, 位置 是线的顶边 在基线之上的距离
+    underline: Option<(f32, f32)>,
+    strikeout: Option<(f32, f32)>,
+}
+
+impl std::fmt::Debug for Font {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Font")
+            .field("glyph_count", &self.inner.glyph_count())
+            .field("family", &self.family)
+            .field("weight", &self.weight)
+            .field("style", &self.style)
+            .finish()
+    }
+}
+
+impl Font {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        // 字重, 样式 和 装饰线的度量 只从字体的 OS/2 和 post 表中读取一次
+        let face =
+            ttf_parser::Face::from_slice(&data, 0).map_err(|e| Error::FontError(e.to_string()))?;
+        let units_per_em = face.units_per_em().max(1) as f32;
+        let line = |metrics: ttf_parser::LineMetrics| {
+            (
+                metrics.position as f32 / units_per_em,
+                metrics.thickness as f32 / units_per_em,
+            )
+        };
+        let family = face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::FAMILY)
+            .find_map(|name| name.to_string())
+            .unwrap_or_default();
+        let weight = FontWeight(face.weight().to_number());
+        let style = if face.is_italic() {
+            FontStyle::Italic
+        } else {
+            FontStyle::Normal
+        };
+        let underline = face.underline_metrics().map(line);
+        let strikeout = face.strikeout_metrics().map(line);
+
+        FontArc::try_from_vec(data)
+            .map(|inner| Self {
+                inner,
+                family,
+                weight,
+                style,
+                underline,
+                strikeout,
+            })
+            .map_err(|e| Error::FontError(e.to_string()))
+    }
+
+    #[inline]
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    #[inline]
+    pub fn weight(&self) -> FontWeight {
+        self.weight
+    }
+
+    #[inline]
+    pub fn style(&self) -> FontStyle {
+        self.style
+    }
+
+    /// 字号 (em 方框的高度) 转换为 ab_glyph 使用的 缩放比例
+    fn scale(&self, size: f32) -> PxScale {
+        let units_per_em = self.inner.units_per_em().unwrap_or(1000.0);
+        PxScale::from(size * self.inner.height_unscaled() / units_per_em)
+    }
+
+    /// 字号为 size 时的 行度量
+    pub fn metrics(&self, size: f32) -> FontMetrics {
+        let font = self.inner.as_scaled(self.scale(size));
+        let estimate = FontMetrics::estimate(size);
+        let (underline_position, underline_thickness) = self
+            .underline
+            .map(|(position, thickness)| (position * size, thickness * size))
+            .unwrap_or((estimate.underline_position, estimate.underline_thickness));
+        let (strikeout_position, strikeout_thickness) = self
+            .strikeout
+            .map(|(position, thickness)| (position * size, thickness * size))
+            .unwrap_or((estimate.strikeout_position, estimate.strikeout_thickness));
+        FontMetrics {
+            ascent: font.ascent(),
+            descent: font.descent(),
+            line_gap: font.line_gap(),
+            underline_position,
+            underline_thickness,
+            strikeout_position,
+            strikeout_thickness,
+        }
+    }
+
+    #[inline]
+    pub fn glyph_id(&self, c: char) -> u16 {
+        self.inner.glyph_id(c).0
+    }
+
+    /// 字形的 水平步进宽度
+    pub fn advance(&self, id: u16, size: f32) -> f32 {
+        self.inner
+            .as_scaled(self.scale(size))
+            .h_advance(ab_glyph::GlyphId(id))
+    }
+
+    /// 相邻两个字形之间的 字距调整
+    pub fn kern(&self, first: u16, second: u16, size: f32) -> f32 {
+        self.inner
+            .as_scaled(self.scale(size))
+            .kern(ab_glyph::GlyphId(first), ab_glyph::GlyphId(second))
+    }
+
+    /// 光栅化 字形, 返回 None 表示 没有轮廓 (如空格)
+    pub fn rasterize(&self, id: u16, size: f32) -> Option<RasterGlyph> {
+        let glyph = ab_glyph::GlyphId(id).with_scale(self.scale(size));
+        let outlined = self.inner.outline_glyph(glyph)?;
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as u32;
+        let height = bounds.height() as u32;
+        let mut coverage = vec![0u8; (width * height) as usize];
+        outlined.draw(|x, y, c| {
+            if x < width && y < height {
+                coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0) as u8;
+            }
+        });
+        Some(RasterGlyph {
+            offset: vec2(bounds.min.x, bounds.min.y),
+            width,
+            height,
+            coverage,
+        })
+    }
+}
+
+/// 光栅化后的字形: 灰度覆盖率位图, 行优先
+#[derive(Debug, Clone)]
+pub struct RasterGlyph {
+    /// 位图左上角 相对于 字形基线起点 的偏移
+    pub offset: Vec2,
+    pub width: u32,
+    pub height: u32,
+    pub coverage: Vec<u8>,
+}
+
+/// 行度量, 基线之上为正, descent 为负数 (基线以下)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+    /// 下划线 和 删除线 顶边的位置, 以及 粗细
+    pub underline_position: f32,
+    pub underline_thickness: f32,
+    pub strikeout_position: f32,
+    pub strikeout_thickness: f32,
+}
+
+impl FontMetrics {
+    /// 没有加载字体 (或字体中没有) 时 估算的度量
+    fn estimate(size: f32) -> Self {
+        Self {
+            ascent: size * 0.8,
+            descent: -size * 0.2,
+            line_gap: 0.0,
+            underline_position: -size * 0.1,
+            underline_thickness: size / 16.0,
+            strikeout_position: size * 0.3,
+            strikeout_thickness: size / 16.0,
+        }
+    }
+
+    #[inline]
+    pub fn height(&self) -> f32 {
+        self.ascent - self.descent
+    }
+}
+
+/// 文本度量: 布局 通过它 计算文本的尺寸, 不依赖 GPU;
+/// Painter 提供 绘制时使用的度量, 保证 布局和绘制 使用相同的字体
+pub trait TextMeasurer {
+    /// 在字族中 选择 样式相同 且 字重最接近的字体, None 表示 没有可用的字体
+    fn select_font(
+        &self,
+        family: Option<&str>,
+        weight: FontWeight,
+        style: FontStyle,
+    ) -> Option<FontId>;
+
+    /// 字号为 size 时的 行度量
+    fn metrics(&self, font: Option<FontId>, size: f32) -> FontMetrics;
+
+    /// 字符的 字形 和 水平步进宽度
+    fn glyph(&self, font: Option<FontId>, c: char, size: f32) -> (u16, f32);
+
+    /// 相邻两个字形之间的 字距调整
+    fn kern(&self, _font: Option<FontId>, _first: u16, _second: u16, _size: f32) -> f32 {
+        0.0
+    }
+
+    /// 字体集合的版本, 加载字体后 改变; 版本改变时 之前的排版结果 失效
+    fn revision(&self) -> usize {
+        0
+    }
+}
+
+/// 基于 TrueType/OpenType 字体文件的 文本度量, 纯 CPU 实现, 也用于 光栅化字形;
+/// 没有加载字体时 按估算的宽度 度量
+#[derive(Debug, Default)]
+pub struct FontSystem {
+    fonts: RefCell<Vec<Font>>,
+}
+
+thread_local! {
+    static SHARED_FONTS: Rc<FontSystem> = Rc::new(FontSystem::new());
+    static MEASURER: RefCell<Rc<dyn TextMeasurer>> = RefCell::new(FontSystem::shared());
+}
+
+impl FontSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前线程 共享的字体集合, Painter 和 LayoutEngine 默认使用它
+    pub fn shared() -> Rc<Self> {
+        SHARED_FONTS.with(|fonts| fonts.clone())
+    }
+
+    /// 加载字体, 第一个加载的字体 是默认字体
+    pub fn load(&self, data: Vec<u8>) -> Result<FontId> {
+        let font = Font::from_bytes(data)?;
+        let mut fonts = self.fonts.borrow_mut();
+        fonts.push(font);
+        Ok(FontId(fonts.len() - 1))
+    }
+
+    /// 已加载的字体
+    pub fn font(&self, id: FontId) -> Option<Font> {
+        self.fonts.borrow().get(id.0).cloned()
+    }
+
+    fn with_font<T>(&self, font: Option<FontId>, f: impl FnOnce(&Font) -> T) -> Option<T> {
+        self.fonts.borrow().get(font?.0).map(f)
+    }
+}
+
+impl TextMeasurer for FontSystem {
+    /// 字族 没有加载时 在所有字体中选择; 没有匹配的样式时 使用 字重最接近的字体, 不合成 粗体和斜体
+    fn select_font(
+        &self,
+        family: Option<&str>,
+        weight: FontWeight,
+        style: FontStyle,
+    ) -> Option<FontId> {
+        let fonts = self.fonts.borrow();
+        let in_family = |font: &Font| match family {
+            Some(family) => font.family.eq_ignore_ascii_case(family.trim()),
+            None => true,
+        };
+        let loaded = fonts.iter().any(in_family);
+        fonts
+            .iter()
+            .enumerate()
+            .filter(|(_, font)| !loaded || in_family(font))
+            .min_by_key(|(_, font)| {
+                let style_mismatch = (font.style != style) as u32;
+                let weight_distance = (font.weight.0 as i32 - weight.0 as i32).unsigned_abs();
+                (style_mismatch, weight_distance)
+            })
+            .map(|(i, _)| FontId(i))
+    }
+
+    fn metrics(&self, font: Option<FontId>, size: f32) -> FontMetrics {
+        self.with_font(font, |font| font.metrics(size))
+            .unwrap_or_else(|| FontMetrics::estimate(size))
+    }
+
+    /// 没有字体时 中日韩文字 宽 1em, 其他字符 宽 0.5em
+    fn glyph(&self, font: Option<FontId>, c: char, size: f32) -> (u16, f32) {
+        self.with_font(font, |font| {
+            let id = font.glyph_id(c);
+            (id, font.advance(id, size))
+        })
+        .unwrap_or_else(|| (0, if is_wide(c) { size } else { size * 0.5 }))
+    }
+
+    fn kern(&self, font: Option<FontId>, first: u16, second: u16, size: f32) -> f32 {
+        self.with_font(font, |font| font.kern(first, second, size))
+            .unwrap_or(0.0)
+    }
+
+    fn revision(&self) -> usize {
+        self.fonts.borrow().len()
+    }
+}
+
+/// 加载字体 到 当前线程共享的字体集合
+pub fn load_font(data: Vec<u8>) -> Result<FontId> {
+    FontSystem::shared().load(data)
+}
+
+/// 当前的文本度量, 布局时 由 LayoutEngine 设置
+pub fn current_measurer() -> Rc<dyn TextMeasurer> {
+    MEASURER.with(|measurer| measurer.borrow().clone())
+}
+
+pub fn set_current_measurer(measurer: Rc<dyn TextMeasurer>) {
+    MEASURER.with(|current| *current.borrow_mut() = measurer);
+}
+
+/// 区分 文本度量 和 它的版本, 改变时 需要重新排版
+pub(crate) fn measurer_key(measurer: &Rc<dyn TextMeasurer>) -> (usize, usize) {
+    (
+        Rc::as_ptr(measurer) as *const () as usize,
+        measurer.revision(),
+    )
+}
+
+/// 全角字符 (中日韩文字 和 全角符号)
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD)
+}
+
+/// 常用文字的 非间距组合符 (Unicode Mn/Me 类), 叠加在 前一个字符上
+fn is_combining_mark(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x05BF
+        | 0x05C1..=0x05C2
+        | 0x05C4..=0x05C5
+        | 0x05C7
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x0670
+        | 0x06D6..=0x06DC
+        | 0x06DF..=0x06E4
+        | 0x06E7..=0x06E8
+        | 0x06EA..=0x06ED
+        | 0x0900..=0x0902
+        | 0x093A
+        | 0x093C
+        | 0x0941..=0x0948
+        | 0x094D
+        | 0x0951..=0x0957
+        | 0x0962..=0x0963
+        | 0x0E31
+        | 0x0E34..=0x0E3A
+        | 0x0E47..=0x0E4E
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x20D0..=0x20FF
+        | 0x3099..=0x309A
+        | 0xFE20..=0xFE2F)
+}
+
+/// 不可见的 格式字符: 零宽空格 和 连接符, 变体选择符, 标签; 没有字形 和 步进宽度
+fn is_invisible(c: char) -> bool {
+    matches!(c as u32,
+        0x200B..=0x200F
+        | 0xFE00..=0xFE0F
+        | 0xE0020..=0xE007F
+        | 0xE0100..=0xE01EF)
+}
+
+/// c 和 前一个字符 属于同一个字素簇, 近似 Unicode 字素簇 (UAX #29) 的扩展规则:
+/// 组合符, 零宽连接符 (ZWJ) 和 它连接的字符, 变体选择符, 表情符号的 肤色修饰符 和 标签
+fn extends_grapheme(prev: Option<char>, c: char) -> bool {
+    match prev {
+        Some(prev) => {
+            prev == '\u{200D}'
+                || is_combining_mark(c)
+                || matches!(c as u32,
+                    0x200D
+                    | 0xFE00..=0xFE0F
+                    | 0x1F3FB..=0x1F3FF
+                    | 0xE0020..=0xE007F
+                    | 0xE0100..=0xE01EF)
+        }
+        None => false,
+    }
+}
+
+/// 排版后的 一个字形
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub font: FontId,
+    pub id: u16,
+    /// 字形在 基线上的 起点
+    pub position: Vec2,
+    /// 字号
+    pub size: f32,
+}
+
+/// 富文本中的一段, 样式为 None 时 使用 段落的样式
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub color: Option<Color>,
+    pub size: Option<f32>,
+    pub weight: Option<FontWeight>,
+    pub style: Option<FontStyle>,
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// 背景高亮的颜色
+    pub background: Option<Color>,
+}
+
+impl Span {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn color(mut self, color: impl Into<Color>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn weight(mut self, weight: impl Into<FontWeight>) -> Self {
+        self.weight = Some(weight.into());
+        self
+    }
+
+    pub fn bold(self) -> Self {
+        self.weight(FontWeight::BOLD)
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.style = Some(FontStyle::Italic);
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    pub fn background(mut self, color: impl Into<Color>) -> Self {
+        self.background = Some(color.into());
+        self
+    }
+}
+
+impl From<&str> for Span {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Span {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// 一个字符 排版的结果, 字素簇 由 连续的 extend 字符 组成
+#[derive(Debug, Clone, Copy)]
+struct Cluster {
+    /// 在文本中的 字节位置
+    index: usize,
+    /// 所属片段的索引
+    span: usize,
+    /// 字形, None 表示 不需要绘制 (空白 和 控制字符)
+    glyph: Option<u16>,
+    /// 水平步进宽度, 包括 和下一个字形的 字距调整
+    advance: f32,
+    whitespace: bool,
+    /// 和前一个字符 属于同一个字素簇, 之前 不能换行
+    extend: bool,
+}
+
+/// 字形排版: 把字符 转换为 字形和步进宽度, 字符位置 从 offset 开始;
+/// 不做 连字 和 字形定位, 组合符 没有步进宽度, 绘制在 前一个字符之后 (字体中 组合符的字形 在原点左侧)
+fn shape(
+    measurer: &dyn TextMeasurer,
+    text: &str,
+    offset: usize,
+    span: usize,
+    font: Option<FontId>,
+    size: f32,
+) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = Vec::with_capacity(text.len());
+    // 上一个 不是组合符的字形, 用于 字距调整
+    let mut prev_glyph = None;
+    let mut prev_char = None;
+    for (index, c) in text.char_indices() {
+        let mark = is_combining_mark(c);
+        let glyph = if c.is_control() || is_invisible(c) {
+            None
+        } else {
+            let (id, advance) = measurer.glyph(font, c, size);
+            if let (Some(prev), Some(prev_id), false) = (clusters.last_mut(), prev_glyph, mark) {
+                prev.advance += measurer.kern(font, prev_id, id, size);
+            }
+            Some((id, if mark { 0.0 } else { advance }))
+        };
+        let glyph_id = glyph.map(|(id, _)| id).filter(|_| !c.is_whitespace());
+        if !mark {
+            prev_glyph = glyph_id;
+        }
+        clusters.push(Cluster {
+            index: offset + index,
+            span,
+            glyph: glyph_id,
+            advance: glyph.map_or(0.0, |(_, advance)| advance),
+            whitespace: c.is_whitespace(),
+            extend: extends_grapheme(prev_char, c),
+        });
+        prev_char = Some(c);
+    }
+    clusters
+}
+
+/// 文本中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    /// 在文本中的 字节范围, 富文本中 是所有片段 连接后的位置
+    pub range: Range<usize>,
+    /// 不包括 行尾空白 的宽度
+    pub width: f32,
+    /// 行的顶边, 相对于 文本区域的顶边
+    pub top: f32,
+    /// 基线, 相对于 文本区域的顶边
+    pub baseline: f32,
+}
+
+/// 一行中 属于同一个片段的 连续字形
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRun {
+    /// 所属片段的索引
+    pub span: usize,
+    /// 在 TextLayout::glyphs 中的范围
+    pub glyphs: Range<usize>,
+    /// 背景高亮的区域: 水平为 字符的步进宽度 (不包括行尾空白), 垂直为 字体的 ascent 到 descent
+    pub rect: Box2,
+    /// 下划线 和 删除线 的区域, 片段没有设置时 为 None
+    pub underline: Option<Box2>,
+    pub strikethrough: Option<Box2>,
+}
+
+/// 文本排版的结果, 位置 相对于 文本区域的左上角
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+    /// 需要绘制的字形
+    pub glyphs: Vec<Glyph>,
+    pub runs: Vec<GlyphRun>,
+    pub size: Vec2,
+}
+
+/// 段落样式, 也是 片段的默认样式
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle {
+    /// 字号
+    pub size: f32,
+    /// 行高 相对于 字号的倍数, 每个片段 按自己的字号计算
+    pub line_height: f32,
+    /// 字族, None 表示 不限制字族
+    pub family: Option<String>,
+    pub weight: FontWeight,
+    pub style: FontStyle,
+    pub align: TextAlign,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 16.0,
+            line_height: 1.2,
+            family: None,
+            weight: FontWeight::default(),
+            style: FontStyle::default(),
+            align: TextAlign::default(),
+        }
+    }
+}
+
+/// 片段 选择的字体 和 字号
+struct RunFont {
+    font: Option<FontId>,
+    size: f32,
+    metrics: FontMetrics,
+}
+
+/// 字形排版后的段落: 所有片段 连接后的文本 和 字符
+struct Paragraph {
+    text: String,
+    clusters: Vec<Cluster>,
+    fonts: Vec<RunFont>,
+}
+
+impl Paragraph {
+    fn new(measurer: &dyn TextMeasurer, spans: &[Span], style: &TextStyle) -> Self {
+        let mut text = String::new();
+        let mut clusters = Vec::new();
+        let mut fonts = Vec::with_capacity(spans.len());
+        for (i, span) in spans.iter().enumerate() {
+            let size = span.size.unwrap_or(style.size);
+            let font = measurer.select_font(
+                style.family.as_deref(),
+                span.weight.unwrap_or(style.weight),
+                span.style.unwrap_or(style.style),
+            );
+            clusters.extend(shape(measurer, &span.text, text.len(), i, font, size));
+            text.push_str(&span.text);
+            let metrics = measurer.metrics(font, size);
+            fonts.push(RunFont {
+                font,
+                size,
+                metrics,
+            });
+        }
+        Self {
+            text,
+            clusters,
+            fonts,
+        }
+    }
+}
+
+/// 可以换行的片段 (两个换行机会之间)
+struct Segment {
+    /// 字符 在 clusters 中的范围
+    clusters: Range<usize>,
+    /// 不包括 行尾空白 的宽度
+    width: f32,
+    /// 包括 行尾空白 的宽度
+    advance: f32,
+    /// 片段之后 必须换行
+    mandatory: bool,
+}
+
+fn segments(text: &str, clusters: &[Cluster]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for (index, opportunity) in linebreaks(text) {
+        let end = clusters.partition_point(|c| c.index < index);
+        // 不在 字素簇 中间 换行
+        if end == start || matches!(clusters.get(end), Some(c) if c.extend) {
+            continue;
+        }
+        let advance = clusters[start..end].iter().map(|c| c.advance).sum::<f32>();
+        let trailing = clusters[start..end]
+            .iter()
+            .rev()
+            .take_while(|c| c.whitespace)
+            .map(|c| c.advance)
+            .sum::<f32>();
+        segments.push(Segment {
+            clusters: start..end,
+            width: advance - trailing,
+            advance,
+            mandatory: opportunity == BreakOpportunity::Mandatory && end < clusters.len(),
+        });
+        start = end;
+    }
+    segments
+}
+
+/// 分行: 每行是 clusters 中的一个范围, 以及 不包括行尾空白的宽度;
+/// 片段 超出行宽时 换到下一行, 单个片段 超出行宽时 在字素簇之间 换行
+fn break_lines(paragraph: &Paragraph, max_width: Option<f32>) -> Vec<(Range<usize>, f32)> {
+    let clusters = &paragraph.clusters;
+    let limit = max_width.unwrap_or(f32::INFINITY);
+    let mut lines: Vec<(Range<usize>, f32)> = Vec::new();
+    let mut line_start = 0;
+    let mut line_end = 0;
+    let mut line_width = 0.0;
+    // 包括 行尾空白 的宽度
+    let mut line_advance = 0.0;
+    for segment in segments(&paragraph.text, clusters) {
+        if line_end > line_start && line_advance + segment.width > limit {
+            lines.push((line_start..line_end, line_width));
+            line_start = line_end;
+            line_width = 0.0;
+            line_advance = 0.0;
+        }
+
+        if line_end == line_start && segment.width > limit {
+            // 单个片段 超出行宽, 在字符之间换行, 每行至少一个字符
+            for i in segment.clusters.clone() {
+                let advance = clusters[i].advance;
+                if i > line_start
+                    && !clusters[i].whitespace
+                    && !clusters[i].extend
+                    && line_advance + advance > limit
+                {
+                    lines.push((line_start..i, line_width));
+                    line_start = i;
+                    line_width = 0.0;
+                    line_advance = 0.0;
+                }
+                line_advance += advance;
+                if !clusters[i].whitespace {
+                    line_width = line_advance;
+                }
+            }
+        } else {
+            line_width = line_advance + segment.width;
+            line_advance += segment.advance;
+        }
+        line_end = segment.clusters.end;
+
+        if segment.mandatory {
+            lines.push((line_start..line_end, line_width));
+            line_start = line_end;
+            line_width = 0.0;
+            line_advance = 0.0;
+        }
+    }
+    if line_end > line_start {
+        lines.push((line_start..line_end, line_width));
+    }
+    lines
+}
+
+/// 文本的 最小内容宽度 (最长的不可换行片段) 和 最大内容宽度 (不自动换行时 最宽的行)
+pub fn measure_text(text: &str, style: &TextStyle) -> (f32, f32) {
+    measure_spans(&[Span::new(text)], style)
+}
+
+/// 富文本的 最小内容宽度 和 最大内容宽度, 使用 当前的文本度量
+pub fn measure_spans(spans: &[Span], style: &TextStyle) -> (f32, f32) {
+    let paragraph = Paragraph::new(current_measurer().as_ref(), spans, style);
+    let min_content = segments(&paragraph.text, &paragraph.clusters)
+        .iter()
+        .map(|s| s.width)
+        .fold(0.0, f32::max);
+    let max_content = break_lines(&paragraph, None)
+        .iter()
+        .map(|line| line.1)
+        .fold(0.0, f32::max);
+    (min_content, max_content)
+}
+
+/// 排版文本: max_width 为 None 时 只在强制换行处 换行
+pub fn layout_text(text: &str, style: &TextStyle, max_width: Option<f32>) -> TextLayout {
+    layout_spans(&[Span::new(text)], style, max_width)
+}
+
+/// 排版富文本: 所有片段 作为一个段落 分行, 每行的高度 包括 行中所有片段的行高;
+/// 使用 当前的文本度量
+pub fn layout_spans(spans: &[Span], style: &TextStyle, max_width: Option<f32>) -> TextLayout {
+    let paragraph = Paragraph::new(current_measurer().as_ref(), spans, style);
+    let lines = break_lines(&paragraph, max_width);
+    let clusters = &paragraph.clusters;
+    let width = max_width.unwrap_or_else(|| lines.iter().map(|l| l.1).fold(0.0, f32::max));
+
+    let mut layout = TextLayout::default();
+    let byte_index = |i: usize| clusters.get(i).map_or(paragraph.text.len(), |c| c.index);
+    let mut top = 0.0;
+    for (range, line_width) in lines {
+        /*
+            行高: 每个片段的行高 = 字号 * line_height, 字体的 ascent + descent 在片段的行高内 垂直居中;
+            基线 对齐后, 行的高度 包括 所有片段 在基线之上和之下的部分
+        */
+        let mut above: f32 = 0.0;
+        let mut below: f32 = 0.0;
+        for cluster in &clusters[range.clone()] {
+            let run_font = &paragraph.fonts[cluster.span];
+            let line_height = run_font.size * style.line_height;
+            let half_leading = (line_height - run_font.metrics.height()) / 2.0;
+            above = above.max(half_leading + run_font.metrics.ascent);
+            below = below.max(line_height - half_leading - run_font.metrics.ascent);
+        }
+        let baseline = top + above;
+
+        let free = (width - line_width).max(0.0);
+        let x0 = match style.align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => free / 2.0,
+            TextAlign::Right => free,
+        };
+        let line_right = x0 + line_width;
+        let line_runs = layout.runs.len();
+        let mut x = x0;
+        for cluster in &clusters[range.clone()] {
+            let run_font = &paragraph.fonts[cluster.span];
+            let same_run = layout.runs.len() > line_runs
+                && layout.runs.last().map(|run| run.span) == Some(cluster.span);
+            if !same_run {
+                let metrics = &run_font.metrics;
+                layout.runs.push(GlyphRun {
+                    span: cluster.span,
+                    glyphs: layout.glyphs.len()..layout.glyphs.len(),
+                    rect: box2(
+                        vec2(x, baseline - metrics.ascent),
+                        vec2(x, baseline - metrics.descent),
+                    ),
+                    underline: None,
+                    strikethrough: None,
+                });
+            }
+            if let Some(id) = cluster.glyph {
+                layout.glyphs.push(Glyph {
+                    font: run_font.font.unwrap_or(FontId(0)),
+                    id,
+                    position: vec2(x, baseline),
+                    size: run_font.size,
+                });
+            }
+            x += cluster.advance;
+            if let Some(run) = layout.runs.last_mut() {
+                run.glyphs.end = layout.glyphs.len();
+                run.rect.max.x = x.min(line_right).max(run.rect.min.x);
+            }
+        }
+        layout.lines.push(TextLine {
+            range: byte_index(range.start)..byte_index(range.end),
+            width: line_width,
+            top,
+            baseline,
+        });
+        top = baseline + below;
+    }
+    layout.size = vec2(width, top);
+
+    // 下划线 和 删除线: 位置 和粗细 来自 片段的字体
+    for run in layout.runs.iter_mut() {
+        let span = &spans[run.span];
+        let metrics = &paragraph.fonts[run.span].metrics;
+        let baseline = run.rect.max.y + metrics.descent;
+        let line = |position: f32, thickness: f32| {
+            box2(
+                vec2(run.rect.min.x, baseline - position),
+                vec2(run.rect.max.x, baseline - position + thickness),
+            )
+        };
+        if span.underline {
+            run.underline = Some(line(
+                metrics.underline_position,
+                metrics.underline_thickness,
+            ));
+        }
+        if span.strikethrough {
+            run.strikethrough = Some(line(
+                metrics.strikeout_position,
+                metrics.strikeout_thickness,
+            ));
+        }
+    }
+    layout
+}
+
+#[test]
+fn test_text_layout() {
+    use crate::element::Element;
+    use crate::layout::{assert_box, compute_layout};
+
+    // 没有加载字体时, 中日韩文字 宽 1em, 其他字符 宽 0.5em
+    let line_height = 16.0 * 1.2;
+    let layout = layout_text("你好世界 hello", &TextStyle::default(), Some(40.0));
+    let lines = layout
+        .lines
+        .iter()
+        .map(|line| (line.range.clone(), line.width))
+        .collect::<Vec<_>>();
+    // 中文 在字符之间换行, 行尾空白 不计入宽度
+    assert_eq!(lines, vec![(0..6, 32.0), (6..13, 32.0), (13..18, 40.0)]);
+    assert_eq!(layout.size, vec2(40.0, line_height * 3.0));
+    // 第二行的 第一个字形, 从行首 基线处开始; 行高 大于字高 时 上下平分
+    let baseline = layout.lines[1].baseline;
+    assert_eq!(layout.glyphs[2].position, vec2(0.0, baseline));
+    assert!((baseline - (line_height + 1.6 + 12.8)).abs() < 1e-4);
+
+    // 组合符 没有步进宽度, 绘制在 前一个字符之后; 不在 字素簇 中间 换行
+    let layout = layout_text(
+        "e\u{301}e\u{301}e\u{301}",
+        &TextStyle::default(),
+        Some(10.0),
+    );
+    let lines = layout
+        .lines
+        .iter()
+        .map(|line| (line.range.clone(), line.width))
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![(0..3, 8.0), (3..6, 8.0), (6..9, 8.0)]);
+    assert_eq!(layout.glyphs[1].position.x, 8.0);
+    // 零宽连接符 连接的字符 不分开
+    let layout = layout_text("a\u{200D}b", &TextStyle::default(), Some(4.0));
+    assert_eq!(layout.lines.len(), 1);
+    assert_eq!(layout.lines[0].width, 16.0);
+
+    let boxes = compute_layout(
+        Element::new("div").children(vec![
+            Element::new("narrow").width("40").child("你好世界 hello"),
+            Element::text("你好世界 hello").font_size(20.0),
+        ]),
+        vec2(400.0, 300.0),
+    );
+    assert_box(boxes[2], (0.0, 0.0), (40.0, line_height * 3.0));
+    // 空间足够时 收缩到 最大内容宽度
+    assert_box(
+        boxes[3],
+        (0.0, line_height * 3.0),
+        (140.0, line_height * 3.0 + 24.0),
+    );
+}
+
+#[test]
+fn test_rich_text_layout() {
+    use crate::element::Element;
+    use crate::layout::compute_layout;
+
+    let approx = |a: f32, b: f32| assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    let spans = vec![
+        Span::new("日志 "),
+        Span::new("ERROR")
+            .size(24.0)
+            .bold()
+            .underline()
+            .background(0xffff00ff),
+        Span::new(" 连接失败"),
+    ];
+    let layout = layout_spans(&spans, &TextStyle::default(), Some(80.0));
+
+    // 换行位置 跨越片段边界, 行尾空白 属于下一个片段
+    let widths = layout.lines.iter().map(|l| l.width).collect::<Vec<_>>();
+    assert_eq!(widths, vec![32.0, 60.0, 64.0]);
+    // 第二行 包含 24px 的片段, 行高 按 较大的字号 计算
+    approx(layout.lines[1].top, 19.2);
+    approx(layout.lines[1].baseline, 19.2 + 2.4 + 19.2);
+    approx(layout.size.y, 19.2 + 28.8 + 19.2);
+
+    let runs = layout
+        .runs
+        .iter()
+        .map(|run| (run.span, run.rect.min.x, run.rect.max.x))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        runs,
+        vec![
+            (0, 0.0, 32.0),
+            (1, 0.0, 60.0),
+            (2, 60.0, 60.0),
+            (2, 0.0, 64.0)
+        ]
+    );
+    let error = &layout.runs[1];
+    approx(error.rect.min.y, layout.lines[1].baseline - 19.2);
+    approx(error.rect.max.y, layout.lines[1].baseline + 4.8);
+    let underline = error.underline.unwrap();
+    approx(underline.min.y, layout.lines[1].baseline + 2.4);
+    approx(underline.height(), 1.5);
+    assert!(error.strikethrough.is_none() && layout.runs[0].underline.is_none());
+    assert_eq!(layout.glyphs[error.glyphs.clone()].len(), 5);
+
+    let boxes = compute_layout(
+        Element::new("div").child(
+            Element::new("log")
+                .width("80")
+                .child(Element::rich_text(spans)),
+        ),
+        vec2(400.0, 300.0),
+    );
+    approx(boxes[2].height(), 67.2);
+    assert_eq!(boxes[2].width(), 80.0);
+}
+
+#[test]
+fn test_text_measurer() {
+    use crate::element::Element;
+    use crate::layout::{assert_box, LayoutEngine};
+
+    /// 等宽字体: 每个字符 宽 0.625em
+    struct Monospace;
+
+    impl TextMeasurer for Monospace {
+        fn select_font(
+            &self,
+            _family: Option<&str>,
+            _weight: FontWeight,
+            _style: FontStyle,
+        ) -> Option<FontId> {
+            None
+        }
+
+        fn metrics(&self, _font: Option<FontId>, size: f32) -> FontMetrics {
+            FontMetrics {
+                ascent: size * 0.75,
+                descent: -size * 0.25,
+                line_gap: 0.0,
+                underline_position: 0.0,
+                underline_thickness: 1.0,
+                strikeout_position: size * 0.25,
+                strikeout_thickness: 1.0,
+            }
+        }
+
+        fn glyph(&self, _font: Option<FontId>, _c: char, size: f32) -> (u16, f32) {
+            (0, size * 0.625)
+        }
+    }
+
+    let (render_tree, _, _) = Element::new("div")
+        .children(vec![
+            Element::text("hello world"),
+            Element::new("narrow")
+                .width("60")
+                .child(Element::text("hello world")),
+        ])
+        .build();
+    let mut engine = LayoutEngine::new();
+    engine.set_text_measurer(Rc::new(Monospace));
+    let boxes = |engine: &mut LayoutEngine| {
+        engine.compute(&render_tree, vec2(400.0, 300.0));
+        render_tree
+            .descendants()
+            .map(|node| node.borrow().dom().ava_box())
+            .collect::<Vec<_>>()
+    };
+
+    let b = boxes(&mut engine);
+    assert_box(b[1], (0.0, 0.0), (110.0, 19.2));
+    // 在空格处 换行
+    assert_box(b[3], (0.0, 19.2), (60.0, 19.2 * 2.0 + 19.2));
+
+    // 更换 文本度量 后 重新排版, 不使用 之前的排版结果
+    engine.set_text_measurer(Rc::new(FontSystem::new()));
+    let b = boxes(&mut engine);
+    assert_box(b[1], (0.0, 0.0), (88.0, 19.2));
+    assert_box(b[3], (0.0, 19.2), (60.0, 19.2 * 2.0 + 19.2));
+}
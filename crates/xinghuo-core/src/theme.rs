@@ -0,0 +1,279 @@
+//! 主题: 命名的 颜色, 间距, 圆角 和 字体 令牌
+//!
+//! 样式声明中 通过 `var(--类别-名称)` 引用令牌, 如: `background-color: var(--color-primary)`,
+//! `padding: var(--spacing-sm) var(--spacing-md)`; 字体令牌 通过 `typography: body` 应用.
+//! 令牌 在层叠时 才解析, 切换主题后 重新层叠 即可更新 所有节点
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use geom::color::Color;
+
+use crate::prelude::*;
+use crate::text::FontWeight;
+use crate::Size;
+
+/// 字体令牌
+#[derive(Debug, Clone, PartialEq)]
+pub struct Typography {
+    pub font_size: f32,
+    /// 行高 相对于 字号的倍数
+    pub line_height: f32,
+    pub font_weight: FontWeight,
+    /// None 表示 不改变字族
+    pub font_family: Option<String>,
+}
+
+impl Typography {
+    pub fn new(font_size: f32, line_height: f32) -> Self {
+        Self {
+            font_size,
+            line_height,
+            font_weight: FontWeight::NORMAL,
+            font_family: None,
+        }
+    }
+
+    pub fn weight(mut self, font_weight: impl Into<FontWeight>) -> Self {
+        self.font_weight = font_weight.into();
+        self
+    }
+
+    pub fn family(mut self, font_family: impl ToString) -> Self {
+        self.font_family = Some(font_family.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Theme {
+    name: String,
+    colors: HashMap<String, Color>,
+    spacing: HashMap<String, Size>,
+    radii: HashMap<String, Size>,
+    typography: HashMap<String, Typography>,
+}
+
+impl Theme {
+    pub fn new(name: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// 浅色主题, 也是 默认主题
+    pub fn light() -> Self {
+        Self::new("light")
+            .with_color("primary", "#1677ff")
+            .with_color("background", "#ffffff")
+            .with_color("surface", "#f5f5f5")
+            .with_color("text", "#1f1f1f")
+            .with_color("text-secondary", "#595959")
+            .with_color("border", "#d9d9d9")
+            .with_scales()
+    }
+
+    /// 深色主题, 令牌名称 和 浅色主题 相同
+    pub fn dark() -> Self {
+        Self::new("dark")
+            .with_color("primary", "#4096ff")
+            .with_color("background", "#141414")
+            .with_color("surface", "#1f1f1f")
+            .with_color("text", "#f0f0f0")
+            .with_color("text-secondary", "#a6a6a6")
+            .with_color("border", "#424242")
+            .with_scales()
+    }
+
+    /// 内置主题 共用的 间距, 圆角 和 字体
+    fn with_scales(self) -> Self {
+        self.with_spacing("xs", 4.0)
+            .with_spacing("sm", 8.0)
+            .with_spacing("md", 16.0)
+            .with_spacing("lg", 24.0)
+            .with_spacing("xl", 32.0)
+            .with_radius("sm", 2.0)
+            .with_radius("md", 4.0)
+            .with_radius("lg", 8.0)
+            .with_typography("caption", Typography::new(12.0, 1.5))
+            .with_typography("body", Typography::new(14.0, 1.5))
+            .with_typography("title", Typography::new(20.0, 1.4).weight(FontWeight::BOLD))
+            .with_typography(
+                "heading",
+                Typography::new(24.0, 1.3).weight(FontWeight::BOLD),
+            )
+    }
+
+    pub fn with_color(mut self, name: impl ToString, color: impl Into<Color>) -> Self {
+        self.colors.insert(name.to_string(), color.into());
+        self
+    }
+
+    pub fn with_spacing(mut self, name: impl ToString, size: impl Into<Size>) -> Self {
+        self.spacing.insert(name.to_string(), size.into());
+        self
+    }
+
+    pub fn with_radius(mut self, name: impl ToString, size: impl Into<Size>) -> Self {
+        self.radii.insert(name.to_string(), size.into());
+        self
+    }
+
+    pub fn with_typography(mut self, name: impl ToString, typography: Typography) -> Self {
+        self.typography.insert(name.to_string(), typography);
+        self
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn color(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+
+    pub fn spacing(&self, name: &str) -> Option<Size> {
+        self.spacing.get(name).copied()
+    }
+
+    pub fn radius(&self, name: &str) -> Option<Size> {
+        self.radii.get(name).copied()
+    }
+
+    pub fn typography(&self, name: &str) -> Option<&Typography> {
+        self.typography.get(name)
+    }
+
+    /// 令牌的值, 名称 带类别前缀, 如: "color-primary", "spacing-md", "radius-sm"
+    pub fn token(&self, name: &str) -> Option<String> {
+        if let Some(name) = name.strip_prefix("color-") {
+            return self
+                .color(name)
+                .map(|c| format!("#{:02x}{:02x}{:02x}{:02x}", c.r(), c.g(), c.b(), c.a()));
+        }
+        if let Some(name) = name.strip_prefix("spacing-") {
+            return self.spacing(name).map(|size| size.to_string());
+        }
+        if let Some(name) = name.strip_prefix("radius-") {
+            return self.radius(name).map(|size| size.to_string());
+        }
+        None
+    }
+
+    /// 替换 value 中的 var(--name) 和 var(--name, fallback), fallback 中 不能有括号;
+    /// 引用了 不存在的令牌 且没有 fallback 时 返回 None
+    pub fn resolve<'a>(&self, value: &'a str) -> Option<Cow<'a, str>> {
+        if !value.contains("var(") {
+            return Some(Cow::Borrowed(value));
+        }
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find("var(") {
+            result.push_str(&rest[..start]);
+            let end = start + rest[start..].find(')')?;
+            let inner = &rest[start + 4..end];
+            let (name, fallback) = match inner.split_once(',') {
+                Some((name, fallback)) => (name, Some(fallback.trim())),
+                None => (inner, None),
+            };
+            let token = name
+                .trim()
+                .strip_prefix("--")
+                .and_then(|name| self.token(name));
+            match token {
+                Some(token) => result.push_str(&token),
+                None => result.push_str(fallback?),
+            }
+            rest = &rest[end + 1..];
+        }
+        result.push_str(rest);
+        Some(Cow::Owned(result))
+    }
+}
+
+#[test]
+fn test_theme() {
+    use crate::app::AppBuilder;
+    use crate::element::Element;
+    use crate::{Background, Quat};
+
+    let theme = Theme::light().with_spacing("gutter", "calc(50% - 2em)");
+    assert_eq!(
+        theme
+            .resolve("var(--spacing-sm) var(--spacing-md)")
+            .unwrap(),
+        "8 16"
+    );
+    assert_eq!(theme.resolve("var(--color-text)").unwrap(), "#1f1f1fff");
+    assert_eq!(
+        theme.resolve("var(--color-none, #ff0000)").unwrap(),
+        "#ff0000"
+    );
+    assert_eq!(theme.resolve("var(--color-none)"), None);
+    // 表达式 格式化后 可以再次解析
+    let gutter = Size::from(&*theme.token("spacing-gutter").unwrap());
+    assert_eq!(gutter, Size::from("calc(50% - 2em)"));
+
+    let mut app = AppBuilder::new(Element::new("div").children(vec![
+        Element::new("card")
+            .inline_style("background-color: var(--color-surface); padding: var(--spacing-md)")
+            .child(Element::text("hello").inline_style("typography: title")),
+        Element::new("section").inline_style("padding: var(--spacing-gutter) 4"),
+    ]))
+    .with_theme(theme)
+    .build();
+    app.render();
+    let card = app.render_tree.first_child().unwrap();
+    let text = card.first_child().unwrap();
+    assert_eq!(
+        card.borrow().dom().style().background,
+        Background::Color(Color::from("#f5f5f5"))
+    );
+    assert_eq!(card.borrow().dom().style().padding, Quat::from("16"));
+    assert_eq!(text.borrow().dom().inherited().font_size, 20.0);
+    // 带空白的 calc 令牌 用于 多值属性
+    let section = card.next_sibling().unwrap();
+    let padding = section.borrow().dom().style().padding;
+    assert_eq!(padding.top(), gutter);
+    assert_eq!(padding.right(), Size::from(4.0));
+    assert_eq!(padding.bottom(), gutter);
+
+    // 切换主题: 引用令牌的节点 重新解析 并标记为 dirty
+    let dark = Theme::dark()
+        .with_spacing("md", 12.0)
+        .with_typography("title", Typography::new(18.0, 1.4));
+    app.set_theme(dark);
+    assert_eq!(app.theme().name(), "dark");
+    assert!(card.borrow().dom().is_dirty());
+    assert_eq!(
+        card.borrow().dom().style().background,
+        Background::Color(Color::from("#1f1f1f"))
+    );
+    app.render();
+    assert_eq!(card.borrow().dom().style().padding, Quat::from("12"));
+    assert_eq!(text.borrow().dom().inherited().font_size, 18.0);
+    assert_eq!(text.borrow().dom().ava_box().min, vec2(12.0, 12.0));
+
+    // 主题 属于 App: 同一线程的 另一个 App 不受影响, 重新层叠时 仍使用 自己的主题
+    let mut other = AppBuilder::new(
+        Element::new("div")
+            .child(Element::new("card").inline_style("background-color: var(--color-surface)")),
+    )
+    .build();
+    let other_card = other.render_tree.first_child().unwrap();
+    app.set_theme(Theme::light());
+    assert_eq!(other.theme().name(), "light");
+    other.set_state(&other_card, crate::PseudoClass::Hover, true);
+    assert_eq!(
+        other_card.borrow().dom().style().background,
+        Background::Color(Color::from("#f5f5f5"))
+    );
+    app.set_theme(Theme::dark());
+    other.set_state(&other_card, crate::PseudoClass::Hover, false);
+    assert_eq!(
+        other_card.borrow().dom().style().background,
+        Background::Color(Color::from("#f5f5f5"))
+    );
+}
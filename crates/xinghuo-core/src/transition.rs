@@ -0,0 +1,412 @@
+//! 过渡: 样式属性改变时, 在一段时间内 从旧值 平滑地变化到新值
+//!
+//! 每帧布局前 比较 各个过渡属性 和 上一帧显示的值, 不论是 setter, 样式表 还是 交互状态
+//! 改变了属性, 都会从 上一帧显示的值 开始过渡; 过渡中 目标值再次改变时, 从 当前显示的值
+//! 过渡到 新的目标值. setter 写入的值 和 当前显示的值 相同时, 视为 没有改变
+
+use std::time::Duration;
+
+use geom::color::Color;
+
+use crate::prelude::*;
+use crate::stylesheet::Property;
+use crate::{Background, Quat, Size, Style};
+
+/// 缓动曲线: 把 时间进度 (0 到 1) 映射为 插值进度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// 三次贝塞尔曲线的 两个控制点 (x1, y1, x2, y2), x1 和 x2 在 0 到 1 之间
+    CubicBezier(f32, f32, f32, f32),
+    /// 质量为 1 的 阻尼弹簧, 曲线拉伸到 弹簧静止所需的时间; 插值进度 可能超过 1
+    Spring {
+        stiffness: f32,
+        damping: f32,
+    },
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Self::EASE
+    }
+}
+
+/// 偏离目标 小于 0.1% 时 认为弹簧静止: e^-9.21 ≈ 0.0001, 留出 临界阻尼 (1 + ωt) 项的余量
+const SPRING_SETTLE: f32 = 9.21;
+
+impl Easing {
+    pub const EASE: Self = Self::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    pub const EASE_IN: Self = Self::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    pub const EASE_OUT: Self = Self::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    pub const EASE_IN_OUT: Self = Self::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    pub fn spring(stiffness: f32, damping: f32) -> Self {
+        Self::Spring {
+            stiffness: stiffness.max(f32::EPSILON),
+            damping: damping.max(f32::EPSILON),
+        }
+    }
+
+    /// 时间进度 t 对应的 插值进度, t 为 0 时是 0, 为 1 时是 1
+    pub fn sample(&self, t: f32) -> f32 {
+        if t <= 0.0 {
+            return 0.0;
+        }
+        if t >= 1.0 {
+            return 1.0;
+        }
+        match *self {
+            Self::Linear => t,
+            Self::CubicBezier(x1, y1, x2, y2) => bezier(y1, y2, solve_bezier(x1, x2, t)),
+            Self::Spring { stiffness, damping } => {
+                spring(stiffness, damping, t * self.settle_time().unwrap_or(1.0))
+            }
+        }
+    }
+
+    /// 弹簧 基本静止 所需的秒数, 其他曲线 返回 None
+    pub fn settle_time(&self) -> Option<f32> {
+        match *self {
+            Self::Spring { stiffness, damping } => {
+                let omega = stiffness.sqrt();
+                let zeta = damping / (2.0 * omega);
+                // 衰减最慢的 指数项
+                let rate = if zeta <= 1.0 {
+                    zeta * omega
+                } else {
+                    omega * (zeta - (zeta * zeta - 1.0).sqrt())
+                };
+                Some(SPRING_SETTLE / rate)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 一维 三次贝塞尔曲线, 两端为 0 和 1
+fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+}
+
+/// 求 x(s) = x 的参数 s: 先用 牛顿法, 不收敛时 二分
+fn solve_bezier(x1: f32, x2: f32, x: f32) -> f32 {
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < 1e-6 {
+            return s;
+        }
+        let r = 1.0 - s;
+        let slope = 3.0 * r * r * x1 + 6.0 * r * s * (x2 - x1) + 3.0 * s * s * (1.0 - x2);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s -= error / slope;
+    }
+    let (mut low, mut high) = (0.0, 1.0);
+    s = x;
+    for _ in 0..32 {
+        if bezier(x1, x2, s) < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+    s
+}
+
+/// 从 0 出发, 初速度为 0, 目标为 1 的 阻尼弹簧 在 time 秒时的位置
+fn spring(stiffness: f32, damping: f32, time: f32) -> f32 {
+    let omega = stiffness.sqrt();
+    let zeta = damping / (2.0 * omega);
+    if zeta < 1.0 {
+        // 欠阻尼: 围绕目标 振荡衰减
+        let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+        let (sin, cos) = (omega_d * time).sin_cos();
+        1.0 - (-zeta * omega * time).exp() * (cos + zeta * omega / omega_d * sin)
+    } else if zeta == 1.0 {
+        1.0 - (-omega * time).exp() * (1.0 + omega * time)
+    } else {
+        // 过阻尼: 两个指数项 叠加
+        let root = (zeta * zeta - 1.0).sqrt();
+        let r1 = -omega * (zeta - root);
+        let r2 = -omega * (zeta + root);
+        1.0 - (r2 * (r1 * time).exp() - r1 * (r2 * time).exp()) / (r2 - r1)
+    }
+}
+
+/// 过渡的 时长, 缓动曲线 和 开始前的延迟
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub duration: Duration,
+    pub easing: Easing,
+    pub delay: Duration,
+}
+
+impl Transition {
+    pub fn new(duration: Duration, easing: Easing) -> Self {
+        Self {
+            duration,
+            easing,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// 弹簧过渡, 时长为 弹簧静止 所需的时间
+    pub fn spring(stiffness: f32, damping: f32) -> Self {
+        let easing = Easing::spring(stiffness, damping);
+        let duration = Duration::from_secs_f32(easing.settle_time().unwrap_or_default());
+        Self::new(duration, easing)
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// 开始后 经过 elapsed 时的 插值进度
+    pub fn progress(&self, elapsed: Duration) -> f32 {
+        if elapsed < self.delay {
+            return 0.0;
+        }
+        let elapsed = elapsed - self.delay;
+        if elapsed >= self.duration {
+            return 1.0;
+        }
+        self.easing
+            .sample(elapsed.as_secs_f32() / self.duration.as_secs_f32())
+    }
+
+    #[inline]
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        elapsed >= self.delay + self.duration
+    }
+}
+
+/// 可以插值的 属性值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimatedValue {
+    Number(f32),
+    Size(Size),
+    Quat(Quat),
+    Color(Color),
+}
+
+impl AnimatedValue {
+    /// 属性的当前值, 属性不能插值 或者 值为 None 时 返回 None
+    pub fn get(style: &Style, property: Property) -> Option<Self> {
+        Some(match property {
+            Property::Width => Self::Size(style.width),
+            Property::Height => Self::Size(style.height),
+            Property::MinWidth => Self::Size(style.min_width?),
+            Property::MaxWidth => Self::Size(style.max_width?),
+            Property::MinHeight => Self::Size(style.min_height?),
+            Property::MaxHeight => Self::Size(style.max_height?),
+            Property::AspectRatio => Self::Number(style.aspect_ratio?),
+            Property::Padding => Self::Quat(style.padding),
+            Property::Margin => Self::Quat(style.margin),
+            Property::BorderWidth => Self::Quat(style.border_width),
+            Property::BorderRadius => Self::Quat(style.border_radius),
+            Property::BorderColor => Self::Color(style.border_color),
+            Property::BackgroundColor => match style.background {
+                Background::Color(color) => Self::Color(color),
+                Background::Gradient(_) => return None,
+            },
+            Property::FlexGrow => Self::Number(style.flex_grow),
+            Property::FlexShrink => Self::Number(style.flex_shrink),
+            Property::FlexBasis => Self::Size(style.flex_basis?),
+            Property::Gap => Self::Size(style.gap),
+            Property::FontSize => Self::Number(style.font_size?),
+            Property::LineHeight => Self::Number(style.line_height?),
+            Property::Color => Self::Color(style.color?),
+            Property::Top => Self::Size(style.top?),
+            Property::Right => Self::Size(style.right?),
+            Property::Bottom => Self::Size(style.bottom?),
+            Property::Left => Self::Size(style.left?),
+            _ => return None,
+        })
+    }
+
+    /// 写入属性, 值的类型 和 属性不匹配时 忽略
+    pub fn set(self, style: &mut Style, property: Property) {
+        match (property, self) {
+            (Property::Width, Self::Size(size)) => style.width = size,
+            (Property::Height, Self::Size(size)) => style.height = size,
+            (Property::MinWidth, Self::Size(size)) => style.min_width = Some(size),
+            (Property::MaxWidth, Self::Size(size)) => style.max_width = Some(size),
+            (Property::MinHeight, Self::Size(size)) => style.min_height = Some(size),
+            (Property::MaxHeight, Self::Size(size)) => style.max_height = Some(size),
+            (Property::AspectRatio, Self::Number(n)) => style.aspect_ratio = Some(n),
+            (Property::Padding, Self::Quat(quat)) => style.padding = quat,
+            (Property::Margin, Self::Quat(quat)) => style.margin = quat,
+            (Property::BorderWidth, Self::Quat(quat)) => style.border_width = quat,
+            (Property::BorderRadius, Self::Quat(quat)) => style.border_radius = quat,
+            (Property::BorderColor, Self::Color(color)) => style.border_color = color,
+            (Property::BackgroundColor, Self::Color(color)) => {
+                style.background = Background::Color(color)
+            }
+            (Property::FlexGrow, Self::Number(n)) => style.flex_grow = n,
+            (Property::FlexShrink, Self::Number(n)) => style.flex_shrink = n,
+            (Property::FlexBasis, Self::Size(size)) => style.flex_basis = Some(size),
+            (Property::Gap, Self::Size(size)) => style.gap = size,
+            (Property::FontSize, Self::Number(n)) => style.font_size = Some(n),
+            (Property::LineHeight, Self::Number(n)) => style.line_height = Some(n),
+            (Property::Color, Self::Color(color)) => style.color = Some(color),
+            (Property::Top, Self::Size(size)) => style.top = Some(size),
+            (Property::Right, Self::Size(size)) => style.right = Some(size),
+            (Property::Bottom, Self::Size(size)) => style.bottom = Some(size),
+            (Property::Left, Self::Size(size)) => style.left = Some(size),
+            _ => {}
+        }
+    }
+
+    /// 线性插值, 类型不同 或者 有 auto 时 不能插值, 返回 None
+    pub fn lerp(&self, other: &Self, t: f32) -> Option<Self> {
+        match (self, other) {
+            (Self::Number(a), Self::Number(b)) => Some(Self::Number(a + (b - a) * t)),
+            (Self::Size(a), Self::Size(b)) => a.lerp(b, t).map(Self::Size),
+            (Self::Quat(a), Self::Quat(b)) => a.lerp(b, t).map(Self::Quat),
+            (Self::Color(a), Self::Color(b)) => Some(Self::Color(a.lerp(b, t))),
+            _ => None,
+        }
+    }
+}
+
+/// 节点上 一个属性的过渡 和 它的运行状态
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TransitionState {
+    property: Property,
+    transition: Transition,
+    /// 上一帧 显示的值
+    shown: Option<AnimatedValue>,
+    /// 正在进行的过渡: 起始值, 目标值 和 开始时间
+    running: Option<(AnimatedValue, AnimatedValue, Duration)>,
+    /// 层叠后 需要重新比较 目标值
+    retargeted: bool,
+}
+
+impl TransitionState {
+    pub(crate) fn new(property: Property, transition: Transition) -> Self {
+        Self {
+            property,
+            transition,
+            shown: None,
+            running: None,
+            retargeted: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn property(&self) -> Property {
+        self.property
+    }
+
+    #[inline]
+    pub(crate) fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    /// 层叠 重新写入了属性, 即使 和当前显示的值 相同, 也是 新的目标值
+    #[inline]
+    pub(crate) fn retarget(&mut self) {
+        self.retargeted = true;
+    }
+
+    /// 推进到 now, 插值结果 写入 style, 值改变时 标记 dirty; 返回 过渡是否 仍在进行
+    pub(crate) fn update(&mut self, style: &mut Style, now: Duration, dirty: &mut bool) -> bool {
+        let current = AnimatedValue::get(style, self.property);
+        if std::mem::take(&mut self.retargeted) || current != self.shown {
+            let heading = match self.running {
+                Some((_, to, _)) => Some(to),
+                None => self.shown,
+            };
+            if current != heading {
+                self.running = match (self.shown, current) {
+                    (Some(from), Some(to)) if from != to && from.lerp(&to, 0.0).is_some() => {
+                        Some((from, to, now))
+                    }
+                    // 不能插值: 直接 使用新值
+                    _ => None,
+                };
+            }
+        }
+
+        if let Some((from, to, start)) = self.running {
+            let elapsed = now.saturating_sub(start);
+            let value = if self.transition.is_finished(elapsed) {
+                self.running = None;
+                to
+            } else {
+                from.lerp(&to, self.transition.progress(elapsed))
+                    .unwrap_or(to)
+            };
+            if Some(value) != current {
+                value.set(style, self.property);
+                *dirty = true;
+            }
+        }
+        self.shown = AnimatedValue::get(style, self.property);
+        self.running.is_some()
+    }
+}
+
+#[test]
+fn test_transition() {
+    use crate::DomElement;
+
+    // 缓动曲线
+    assert_eq!(Easing::Linear.sample(0.25), 0.25);
+    assert_eq!(Easing::EASE_IN_OUT.sample(0.0), 0.0);
+    assert_eq!(Easing::EASE_IN_OUT.sample(1.0), 1.0);
+    assert!((Easing::EASE_IN_OUT.sample(0.5) - 0.5).abs() < 1e-4);
+    assert!(Easing::EASE_IN.sample(0.3) < 0.3);
+    assert!(Easing::EASE_OUT.sample(0.3) > 0.3);
+    // 欠阻尼弹簧 会超过目标, 过阻尼弹簧 不会
+    let bouncy = Easing::spring(200.0, 10.0);
+    assert!((0..100).any(|i| bouncy.sample(i as f32 / 100.0) > 1.0));
+    let stiff = Easing::spring(200.0, 40.0);
+    assert!((0..100).all(|i| stiff.sample(i as f32 / 100.0) <= 1.0));
+    assert!((stiff.sample(0.99) - 1.0).abs() < 1e-3);
+
+    let ms = Duration::from_millis;
+    let mut dom = DomElement::new("div");
+    dom.set_width(100.0);
+    dom.set_transition(
+        Property::BackgroundColor,
+        Transition::new(ms(100), Easing::Linear),
+    );
+    dom.set_transition(Property::Width, Transition::new(ms(100), Easing::Linear));
+    assert!(!dom.update_transitions(ms(0)));
+
+    dom.set_background_color(Color::BLACK);
+    dom.set_width(200.0);
+    dom.set_dirty(false);
+    // 改变后的 第一帧 显示 旧值
+    assert!(dom.update_transitions(ms(10)));
+    assert_eq!(dom.style().background, Background::Color(Color::default()));
+    assert_eq!(dom.style().width, Size::new_value(100.0));
+    assert!(dom.is_dirty());
+    assert!(dom.update_transitions(ms(60)));
+    assert_eq!(
+        dom.style().background,
+        Background::Color(Color::from("#808080"))
+    );
+    assert_eq!(dom.style().width, Size::new_value(150.0));
+
+    // 过渡中 改变目标值: 从 当前显示的值 开始
+    dom.set_width(50.0);
+    assert!(dom.update_transitions(ms(60)));
+    assert_eq!(dom.style().width, Size::new_value(150.0));
+    assert!(dom.update_transitions(ms(110)));
+    assert_eq!(dom.style().width, Size::new_value(100.0));
+    assert_eq!(dom.style().background, Background::Color(Color::BLACK));
+
+    // 结束后 停在目标值
+    assert!(!dom.update_transitions(ms(160)));
+    assert_eq!(dom.style().width, Size::new_value(50.0));
+    dom.set_dirty(false);
+    assert!(!dom.update_transitions(ms(200)));
+    assert!(!dom.is_dirty());
+}
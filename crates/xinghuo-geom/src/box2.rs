@@ -3,7 +3,7 @@ use glam::Vec2;
 
 use crate::{rect::Rect, vec2};
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Box2 {
     pub min: Vec2,
     pub max: Vec2,
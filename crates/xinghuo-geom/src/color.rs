@@ -12,6 +12,7 @@ impl Color {
     pub const GLEEN: Self = Self([0x00, 0xff, 0x00, 0xff]);
     pub const BLUE: Self = Self([0x00, 0x00, 0xff, 0xff]);
     pub const YELLOW: Self = Self([0xff, 0xff, 0x00, 0xff]);
+    pub const BLACK: Self = Self([0x00, 0x00, 0x00, 0xff]);
 
     #[inline(always)]
     pub fn r(&self) -> u8 {
@@ -33,6 +34,17 @@ impl Color {
         self.0[3]
     }
 
+    /// 各分量 线性插值, t 为 0 时是 self, 为 1 时是 other
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mut result = *self;
+        for (c, other) in result.0.iter_mut().zip(other.0.iter()) {
+            *c = (*c as f32 + (*other as f32 - *c as f32) * t)
+                .round()
+                .clamp(0.0, 255.0) as u8;
+        }
+        result
+    }
+
     pub fn to_value(&self) -> [f32; 4] {
         [
             self.r() as f32 / 255.0,
@@ -44,11 +56,19 @@ impl Color {
 }
 
 /// 从字符串中获取Rgba
-/// 1. 以'#'开头, 后面是4个8位的十六进制数字, 如: "#12345678"
-/// 2. 4个u8的数组或元组, 如: "[255, 0, 0, 255]" "(255, 0, 0, 255)"
+/// 1. 以'#'开头, 后面是4个8位的十六进制数字, 如: "#12345678", 省略透明度时 不透明, 如: "#123456"
+/// 2. 4个十进制u8的数组或元组, 如: "[255, 0, 0, 255]" "(255, 0, 0, 255)"
 ///
+/// 无法解析时 返回 默认颜色
 impl From<&str> for Color {
     fn from(s: &str) -> Self {
+        // 按字节 截取 十六进制数字, 非 ASCII 字符 会落在 字符边界之内
+        if s.starts_with('#') && !s.is_ascii() {
+            return Self::default();
+        }
+        if s.len() == 7 && s.starts_with('#') {
+            return Self::from(&*format!("{}ff", s));
+        }
         if s.len() == 9 && s.starts_with('#') {
             let r = u8::from_str_radix(&s[1..3], 16).unwrap_or(0xff);
             let g = u8::from_str_radix(&s[3..5], 16).unwrap_or(0xff);
@@ -57,16 +77,16 @@ impl From<&str> for Color {
             return Self([r, g, b, a]);
         }
         let s = s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
-        if s.starts_with('(') && s.starts_with(')') || s.starts_with('[') && s.starts_with(']') {
+        if s.starts_with('(') && s.ends_with(')') || s.starts_with('[') && s.ends_with(']') {
             let s = &s[1..s.len() - 1];
             let s = s.split(',').collect::<Vec<_>>();
             if s.len() != 4 {
                 return Self::default();
             }
-            let r = u8::from_str_radix(s[0], 16).unwrap_or(0xff);
-            let g = u8::from_str_radix(s[1], 16).unwrap_or(0xff);
-            let b = u8::from_str_radix(s[2], 16).unwrap_or(0xff);
-            let a = u8::from_str_radix(s[3], 16).unwrap_or(0xff);
+            let r = s[0].parse::<u8>().unwrap_or(0xff);
+            let g = s[1].parse::<u8>().unwrap_or(0xff);
+            let b = s[2].parse::<u8>().unwrap_or(0xff);
+            let a = s[3].parse::<u8>().unwrap_or(0xff);
             return Self([r, g, b, a]);
         }
         Self::default()
@@ -0,0 +1,98 @@
+use glam::Vec2;
+
+use crate::{vec2, Color, Rect};
+
+/// 渐变的形状, 角度 以度为单位, 0 度 指向上方, 顺时针增加
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    /// 沿 angle 方向的 线性渐变, 渐变线 恰好经过 矩形的两个角
+    Linear { angle: f32 },
+    /// 以 center 为中心 的椭圆渐变, 延伸到 最远的角; center 相对于矩形, 0 到 1
+    Radial { center: Vec2 },
+    /// 围绕 center 从 angle 开始 顺时针一周 的锥形渐变
+    Conic { angle: f32, center: Vec2 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    /// 色标: 位置 (0 到 1) 和 颜色, 位置 不递减
+    pub stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    pub fn new(kind: GradientKind) -> Self {
+        Self {
+            kind,
+            stops: Vec::new(),
+        }
+    }
+
+    pub fn linear(angle: f32) -> Self {
+        Self::new(GradientKind::Linear { angle })
+    }
+
+    pub fn radial() -> Self {
+        Self::new(GradientKind::Radial {
+            center: Vec2::splat(0.5),
+        })
+    }
+
+    pub fn conic(angle: f32) -> Self {
+        Self::new(GradientKind::Conic {
+            angle,
+            center: Vec2::splat(0.5),
+        })
+    }
+
+    /// 添加色标, 位置 小于前一个色标时 取前一个的位置
+    pub fn stop(mut self, offset: f32, color: impl Into<Color>) -> Self {
+        let min = self.stops.last().map(|stop| stop.0).unwrap_or(0.0);
+        self.stops.push((offset.max(min), color.into()));
+        self
+    }
+
+    /// point 在 rect 中的 渐变位置, 和 quad.wgsl 中 fs_gradient 的计算相同
+    pub fn progress(&self, rect: Rect, point: Vec2) -> f32 {
+        match self.kind {
+            GradientKind::Linear { angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let length = (rect.size.x * sin).abs() + (rect.size.y * cos).abs();
+                if length <= 0.0 {
+                    return 0.0;
+                }
+                (point - rect.center()).dot(vec2(sin, -cos)) / length + 0.5
+            }
+            GradientKind::Radial { center } => {
+                let center = rect.origin + center * rect.size;
+                let corner = (center - rect.min()).max(rect.max() - center);
+                let radius = (corner * std::f32::consts::SQRT_2).max(Vec2::splat(1e-5));
+                ((point - center) / radius).length()
+            }
+            GradientKind::Conic { angle, center } => {
+                let d = point - (rect.origin + center * rect.size);
+                let turn = (d.x.atan2(-d.y) - angle.to_radians()) / std::f32::consts::TAU;
+                turn - turn.floor()
+            }
+        }
+    }
+
+    /// point 处的颜色, 色标之间 线性插值, 两端之外 取端点的颜色
+    pub fn color_at(&self, rect: Rect, point: Vec2) -> Color {
+        let t = self.progress(rect, point);
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::from(0),
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, from), (end, to)) = (pair[0], pair[1]);
+            if t <= end {
+                return from.lerp(&to, (t - start) / (end - start).max(1e-5));
+            }
+        }
+        last.1
+    }
+}
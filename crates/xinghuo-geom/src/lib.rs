@@ -4,15 +4,19 @@
 
 pub mod box2;
 pub mod color;
+pub mod gradient;
 pub mod quad;
 pub mod rect;
+pub mod shadow;
 
 pub use glam::Vec2;
 
 pub use box2::Box2;
 pub use color::Color;
+pub use gradient::{Gradient, GradientKind};
 pub use quad::Quad;
 pub use rect::Rect;
+pub use shadow::Shadow;
 
 /// 重新导出 glam
 pub use glam;
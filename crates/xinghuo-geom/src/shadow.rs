@@ -0,0 +1,153 @@
+use glam::Vec2;
+
+use crate::{vec2, Color, Rect};
+
+/// 圆角矩形的 模糊阴影, 外阴影 绘制在矩形外, 内阴影 绘制在矩形内
+///
+/// This type can be directly uploaded to GPU memory.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Shadow {
+    /// 投下阴影的 矩形
+    pub rect: Rect,
+    pub color: [f32; 4],
+    pub offset: [f32; 2],
+    /// 模糊半径, 高斯模糊的 标准差 为 它的一半
+    pub blur: f32,
+    /// 外阴影 向外扩展, 内阴影 向内收缩 的距离
+    pub spread: f32,
+    pub border_radius: f32,
+    /// 大于 0 时 是内阴影
+    pub inset: f32,
+}
+
+impl Shadow {
+    pub fn new(rect: Rect, color: Color) -> Self {
+        Self {
+            rect,
+            color: color.to_value(),
+            offset: Default::default(),
+            blur: Default::default(),
+            spread: Default::default(),
+            border_radius: Default::default(),
+            inset: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn is_inset(&self) -> bool {
+        self.inset > 0.0
+    }
+
+    /// 模糊前的 阴影形状: 平移后 按 spread 扩展或收缩 的矩形, 和它的圆角半径
+    pub fn shape(&self) -> (Rect, f32) {
+        let spread = if self.is_inset() {
+            -self.spread
+        } else {
+            self.spread
+        };
+        let mut rect = self
+            .rect
+            .translate(self.offset.into())
+            .inflate(spread, spread);
+        rect.size = rect.size.max(Vec2::ZERO);
+        let radius = (self.border_radius + spread).max(0.0);
+        (rect, radius.min(rect.size.min_element() / 2.0))
+    }
+
+    /// 需要绘制的区域, 外阴影 包括 3 倍标准差 的模糊范围
+    pub fn bounds(&self) -> Rect {
+        if self.is_inset() {
+            return self.rect;
+        }
+        let extent = 3.0 * self.sigma();
+        self.shape().0.inflate(extent, extent)
+    }
+
+    /// point 处 阴影颜色的 不透明度, 和 quad.wgsl 中 fs_shadow 的计算相同;
+    /// 用于 测试 和 以后的 CPU 绘制, 目前 没有 Painter 在 CPU 上 绘制阴影
+    pub fn alpha(&self, point: Vec2) -> f32 {
+        let (shape, radius) = self.shape();
+        let coverage = rounded_box_shadow(shape, radius, point, self.sigma());
+        let radius = self.border_radius.min(self.rect.size.min_element() / 2.0);
+        let inside = 1.0
+            - smoothstep(
+                radius - 0.5,
+                radius + 0.5,
+                distance(self.rect, radius, point),
+            );
+        if self.is_inset() {
+            (1.0 - coverage) * inside
+        } else {
+            coverage * (1.0 - inside)
+        }
+    }
+
+    /// 没有模糊时 也保留 半个像素的 抗锯齿
+    fn sigma(&self) -> f32 {
+        (self.blur / 2.0).max(0.5)
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Shadow {}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Shadow {}
+
+fn smoothstep(low: f32, high: f32, x: f32) -> f32 {
+    let t = ((x - low) / (high - low)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// point 到 圆角矩形 圆角圆心所围区域 的距离
+fn distance(rect: Rect, radius: f32, point: Vec2) -> f32 {
+    let top_left = rect.origin + Vec2::splat(radius);
+    let bottom_right = rect.origin + rect.size - Vec2::splat(radius);
+    let dist = (top_left - point).max(point - bottom_right).max(Vec2::ZERO);
+    dist.length()
+}
+
+/// 误差函数的 近似
+fn erf(x: Vec2) -> Vec2 {
+    let s = vec2(x.x.signum(), x.y.signum());
+    let a = x.abs();
+    let r =
+        Vec2::ONE + (Vec2::splat(0.278393) + (Vec2::splat(0.230389) + 0.078108 * a * a) * a) * a;
+    let r = r * r;
+    s - s / (r * r)
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    const PI: f32 = std::f32::consts::PI;
+    (-(x * x) / (2.0 * sigma * sigma)).exp() / ((2.0 * PI).sqrt() * sigma)
+}
+
+/// 高斯模糊后的 圆角矩形 在 y 方向的一行上 沿 x 的积分
+fn rounded_box_shadow_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: Vec2) -> f32 {
+    let delta = (half_size.y - corner - y.abs()).min(0.0);
+    let curved = half_size.x - corner + (corner * corner - delta * delta).max(0.0).sqrt();
+    let integral = Vec2::splat(0.5)
+        + 0.5 * erf((Vec2::splat(x) + vec2(-curved, curved)) * (0.5f32.sqrt() / sigma));
+    integral.y - integral.x
+}
+
+/// 高斯模糊后的 圆角矩形 在 point 处的覆盖率: x 方向 解析积分, y 方向 取 4 个样本
+fn rounded_box_shadow(rect: Rect, corner: f32, point: Vec2, sigma: f32) -> f32 {
+    let half_size = rect.size / 2.0;
+    let p = point - (rect.origin + half_size);
+    let low = p.y - half_size.y;
+    let high = p.y + half_size.y;
+    let start = (-3.0 * sigma).clamp(low, high);
+    let end = (3.0 * sigma).clamp(low, high);
+    let step = (end - start) / 4.0;
+    let mut y = start + step * 0.5;
+    let mut value = 0.0;
+    for _ in 0..4 {
+        value += rounded_box_shadow_x(p.x, p.y - y, sigma, corner, half_size)
+            * gaussian(y, sigma)
+            * step;
+        y += step;
+    }
+    value
+}
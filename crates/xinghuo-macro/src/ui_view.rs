@@ -203,8 +203,17 @@ impl TryFrom<syn_rsx::Node> for UiExpr {
 
 impl ToTokens for UiExpr {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let Self { expr } = self;
-        quote!(#[allow(unused_braces)] #expr).to_tokens(tokens);
+        // 子节点 作为 .child() 的参数, 文本 转换为 文本元素
+        // 只有一个表达式的块 {expr} 展开为 expr, 参数位置 不能使用属性 消除 unused_braces 警告
+        match &self.expr {
+            syn::Expr::Block(syn::ExprBlock { block, .. }) if block.stmts.len() == 1 => {
+                match &block.stmts[0] {
+                    syn::Stmt::Expr(expr) => expr.to_tokens(tokens),
+                    stmt => quote!({ #stmt }).to_tokens(tokens),
+                }
+            }
+            expr => expr.to_tokens(tokens),
+        }
     }
 }
 
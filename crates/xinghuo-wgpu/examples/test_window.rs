@@ -1,9 +1,16 @@
-use xinghuo_core::{app::AppBuilder, element::Element, prelude::Color, Layout};
+use xinghuo_core::{app::AppBuilder, element::Element, prelude::Color, text::load_font, Layout};
 use xinghuo_wgpu::window::Window;
 
 fn main() {
     init_log();
 
+    // 字体文件的路径, 如: XINGHUO_FONT=/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc
+    // 没有指定时 WindowPainter 加载 常见的系统字体
+    if let Ok(path) = std::env::var("XINGHUO_FONT") {
+        let data = std::fs::read(&path).expect("读取字体文件失败");
+        load_font(data).expect("加载字体失败");
+    }
+
     let ele = div().children(vec![header(), center(), footer()]);
 
     Window::new(AppBuilder::new(ele))
@@ -34,6 +41,7 @@ fn header() -> Element {
         .onclick(|clicked| {
             println!("{:?}", &clicked);
         })
+        .child(Element::text("你好, 星火 hello xinghuo").font_size(20.0))
 }
 
 fn center() -> Element {
@@ -6,6 +6,7 @@ use xinghuo_core::app::GpuDeviceInfo;
 pub mod error;
 pub mod painter;
 pub mod quad;
+pub mod text;
 pub mod window;
 // mod draw_pipe;
 // pub mod font;
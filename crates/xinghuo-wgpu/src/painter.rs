@@ -1,7 +1,33 @@
+use std::iter;
+use std::ops::Range;
+use std::rc::Rc;
+
 use winit::window::Window;
 use xinghuo_core::prelude::*;
+use xinghuo_core::text::{FontSystem, Glyph, TextMeasurer};
+
+use crate::{quad, text};
 
-use crate::quad;
+/// 一帧中的 绘制命令, 按顺序执行, 范围是 实例的索引
+#[derive(Debug, Clone)]
+enum DrawCommand {
+    Quads {
+        range: Range<u32>,
+        scissor: Option<[u32; 4]>,
+    },
+    Shadows {
+        range: Range<u32>,
+        scissor: Option<[u32; 4]>,
+    },
+    Gradients {
+        range: Range<u32>,
+        scissor: Option<[u32; 4]>,
+    },
+    Glyphs {
+        range: Range<u32>,
+        scissor: Option<[u32; 4]>,
+    },
+}
 
 pub struct WindowPainter {
     surface: wgpu::Surface,
@@ -9,21 +35,88 @@ pub struct WindowPainter {
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     size: [f32; 2],
+    /// 窗口的缩放比例
+    scale_factor: f32,
     /// 绘制四边形
     quad_pipeline: quad::Pipeline,
+    /// 绘制文本
+    text_pipeline: text::Pipeline,
+    /// 光栅化字形 和 布局时 计算文本尺寸 使用的字体
+    fonts: Rc<FontSystem>,
+    /// 当前的裁剪区域
+    clip: Option<Box2>,
+    /// 当前帧 收集的 四边形, 字形 和 绘制命令, 在 render 时 提交
+    quads: Vec<Quad>,
+    shadows: Vec<Shadow>,
+    gradients: Vec<quad::GradientInstance>,
+    glyphs: Vec<text::GlyphInstance>,
+    commands: Vec<DrawCommand>,
 }
 
 impl Painter for WindowPainter {
     fn rect(&mut self, rect: &[Quad]) {
-        if let Ok(output) = self.surface.get_current_texture() {
-            self.quad_pipeline
-                .render(&self.device, output, &self.queue, rect)
-                .unwrap();
+        let start = self.quads.len() as u32;
+        self.quads.extend_from_slice(rect);
+        let range = start..self.quads.len() as u32;
+        let scissor = self.scissor();
+        // 和上一个命令 裁剪区域相同时 合并
+        match self.commands.last_mut() {
+            Some(DrawCommand::Quads {
+                range: last,
+                scissor: last_scissor,
+            }) if last.end == range.start && *last_scissor == scissor => last.end = range.end,
+            _ => self.commands.push(DrawCommand::Quads { range, scissor }),
         }
     }
 
-    fn text(&mut self, text: String, pos: Vec2, size: f32, color: Color) {
-        todo!()
+    fn shadow(&mut self, shadows: &[Shadow]) {
+        let start = self.shadows.len() as u32;
+        self.shadows.extend_from_slice(shadows);
+        let range = start..self.shadows.len() as u32;
+        let scissor = self.scissor();
+        match self.commands.last_mut() {
+            Some(DrawCommand::Shadows {
+                range: last,
+                scissor: last_scissor,
+            }) if last.end == range.start && *last_scissor == scissor => last.end = range.end,
+            _ => self.commands.push(DrawCommand::Shadows { range, scissor }),
+        }
+    }
+
+    fn gradient(&mut self, rect: Rect, border_radius: f32, gradient: &Gradient) {
+        let start = self.gradients.len() as u32;
+        self.gradients
+            .push(quad::GradientInstance::new(rect, border_radius, gradient));
+        let range = start..self.gradients.len() as u32;
+        let scissor = self.scissor();
+        match self.commands.last_mut() {
+            Some(DrawCommand::Gradients {
+                range: last,
+                scissor: last_scissor,
+            }) if last.end == range.start && *last_scissor == scissor => last.end = range.end,
+            _ => self
+                .commands
+                .push(DrawCommand::Gradients { range, scissor }),
+        }
+    }
+
+    fn text(&mut self, glyphs: &[Glyph], color: Color) {
+        let start = self.glyphs.len() as u32;
+        self.text_pipeline
+            .push(&self.queue, &self.fonts, glyphs, color, &mut self.glyphs);
+        let range = start..self.glyphs.len() as u32;
+        let scissor = self.scissor();
+        match self.commands.last_mut() {
+            Some(DrawCommand::Glyphs {
+                range: last,
+                scissor: last_scissor,
+            }) if last.end == range.start && *last_scissor == scissor => last.end = range.end,
+            _ => self.commands.push(DrawCommand::Glyphs { range, scissor }),
+        }
+    }
+
+    fn clip(&mut self, rect: Option<Box2>) {
+        self.clip = rect;
     }
 
     fn resize(&mut self, new_size: [f32; 2]) {
@@ -34,14 +127,111 @@ impl Painter for WindowPainter {
             self.surface.configure(&self.device, &self.config);
             self.quad_pipeline
                 .resize(&self.device, &self.queue, &self.config, &new_size);
+            self.text_pipeline.resize(&self.queue, &new_size);
         }
     }
 
-    fn render(&mut self) {}
+    /// 提交 当前帧 收集的绘制命令
+    fn render(&mut self) {
+        let quads = std::mem::take(&mut self.quads);
+        let shadows = std::mem::take(&mut self.shadows);
+        let gradients = std::mem::take(&mut self.gradients);
+        let glyphs = std::mem::take(&mut self.glyphs);
+        let commands = std::mem::take(&mut self.commands);
+        self.clip = None;
+
+        let output = match self.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(e) => {
+                tracing::warn!("获取 surface 纹理失败: {:?}", e);
+                return;
+            }
+        };
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.quad_pipeline
+            .prepare(&self.queue, &quads, &shadows, &gradients);
+        self.text_pipeline.prepare(&self.queue, &glyphs);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.1,
+                            g: 0.2,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: self.quad_pipeline.depth_view(),
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            let (width, height) = (self.config.width, self.config.height);
+            for command in commands {
+                let (range, scissor) = match &command {
+                    DrawCommand::Quads { range, scissor } => (range.clone(), *scissor),
+                    DrawCommand::Shadows { range, scissor } => (range.clone(), *scissor),
+                    DrawCommand::Gradients { range, scissor } => (range.clone(), *scissor),
+                    DrawCommand::Glyphs { range, scissor } => (range.clone(), *scissor),
+                };
+                // 裁剪区域 [x, y, width, height], 为空时 不绘制
+                match scissor.unwrap_or([0, 0, width, height]) {
+                    [_, _, 0, _] | [_, _, _, 0] => continue,
+                    [x, y, width, height] => render_pass.set_scissor_rect(x, y, width, height),
+                }
+                match command {
+                    DrawCommand::Quads { .. } => self.quad_pipeline.draw(&mut render_pass, range),
+                    DrawCommand::Shadows { .. } => {
+                        self.quad_pipeline.draw_shadows(&mut render_pass, range)
+                    }
+                    DrawCommand::Gradients { .. } => {
+                        self.quad_pipeline.draw_gradients(&mut render_pass, range)
+                    }
+                    DrawCommand::Glyphs { .. } => self.text_pipeline.draw(&mut render_pass, range),
+                }
+            }
+        }
+
+        self.queue.submit(iter::once(encoder.finish()));
+        output.present();
+    }
 
     fn size(&self) -> [f32; 2] {
         self.size
     }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn rescale(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    fn text_measurer(&self) -> Rc<dyn TextMeasurer> {
+        self.fonts.clone()
+    }
 }
 
 impl WindowPainter {
@@ -87,6 +277,14 @@ impl WindowPainter {
             surface.configure(&device, &config);
 
             let quad_pipeline = quad::Pipeline::new(&device, &queue, config.clone(), size);
+            let text_pipeline = text::Pipeline::new(&device, &config, size);
+
+            let fonts = FontSystem::shared();
+            if text::load_fallback_font(&fonts).is_none() {
+                tracing::warn!(
+                    "没有找到字体, 文本只参与布局, 不绘制; 可以用 XINGHUO_FONT 指定字体文件"
+                );
+            }
 
             Self {
                 surface,
@@ -94,11 +292,43 @@ impl WindowPainter {
                 queue,
                 config,
                 size: [size.width as f32, size.height as f32],
+                scale_factor: window.scale_factor() as f32,
                 quad_pipeline,
+                text_pipeline,
+                fonts,
+                clip: None,
+                quads: Vec::new(),
+                shadows: Vec::new(),
+                gradients: Vec::new(),
+                glyphs: Vec::new(),
+                commands: Vec::new(),
             }
         })
     }
 
+    /// 使用 指定的字体集合, 默认是 当前线程共享的字体集合
+    pub fn with_fonts(mut self, fonts: Rc<FontSystem>) -> Self {
+        self.fonts = fonts;
+        self
+    }
+
+    /// 裁剪区域 转换为 不超出 surface 的像素区域 [x, y, width, height]
+    fn scissor(&self) -> Option<[u32; 4]> {
+        self.clip.map(|clip| {
+            let (width, height) = (self.config.width, self.config.height);
+            let min_x = (clip.min.x.max(0.0) as u32).min(width);
+            let min_y = (clip.min.y.max(0.0) as u32).min(height);
+            let max_x = (clip.max.x.max(0.0).ceil() as u32).min(width);
+            let max_y = (clip.max.y.max(0.0).ceil() as u32).min(height);
+            [
+                min_x,
+                min_y,
+                max_x.saturating_sub(min_x),
+                max_y.saturating_sub(min_y),
+            ]
+        })
+    }
+
     // fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
     //     let output = self.surface.get_current_texture()?;
     //     self.quad_pipeline.render(&self.device, output, &self.queue)
@@ -1,11 +1,11 @@
-use std::iter;
+use std::ops::Range;
 use wgpu::util::DeviceExt;
 use winit::dpi::PhysicalSize;
-use xinghuo_core::prelude::{glam::Mat4, Quad};
+use xinghuo_core::prelude::{glam::Mat4, Gradient, GradientKind, Quad, Rect, Shadow};
 
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct Globals {
+pub(crate) struct Globals {
     pub transform: Mat4,
     pub transform_inverse: Mat4,
 }
@@ -50,10 +50,67 @@ const QUAD_VERTS: [Vertex; 4] = [
 ];
 
 // const QUAD_INDICES: [u16; 3] = [0, 1, 2];
-const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+pub(crate) const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+/// 一帧中 最多绘制的 四边形数量
+const MAX_INSTANCES: usize = 100_000;
+
+/// 一帧中 最多绘制的 渐变数量
+const MAX_GRADIENTS: usize = 10_000;
+
+/// 每个渐变 最多的色标数量, 多出的色标 被忽略
+const MAX_STOPS: usize = 8;
+
+/// 渐变填充的矩形, 上传到 GPU 的实例
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GradientInstance {
+    pos: [f32; 2],
+    size: [f32; 2],
+    /// 种类 (0 线性, 1 径向, 2 锥形), 角度 (弧度), 中心 (相对于矩形)
+    params: [f32; 4],
+    colors: [[f32; 4]; MAX_STOPS],
+    /// 不足 MAX_STOPS 个色标时 重复最后一个
+    offsets: [f32; MAX_STOPS],
+    border_radius: f32,
+}
+
+impl GradientInstance {
+    pub fn new(rect: Rect, border_radius: f32, gradient: &Gradient) -> Self {
+        let params = match gradient.kind {
+            GradientKind::Linear { angle } => [0.0, angle.to_radians(), 0.5, 0.5],
+            GradientKind::Radial { center } => [1.0, 0.0, center.x, center.y],
+            GradientKind::Conic { angle, center } => [2.0, angle.to_radians(), center.x, center.y],
+        };
+        let mut instance = Self {
+            pos: rect.origin.into(),
+            size: rect.size.into(),
+            params,
+            colors: Default::default(),
+            offsets: Default::default(),
+            border_radius,
+        };
+        if gradient.stops.len() > MAX_STOPS {
+            tracing::warn!("渐变的色标 超过 {} 个, 多出的被忽略", MAX_STOPS);
+        }
+        let stops = &gradient.stops[..gradient.stops.len().min(MAX_STOPS)];
+        if !stops.is_empty() {
+            let stop_pairs = instance.offsets.iter_mut().zip(&mut instance.colors);
+            for (i, (offset, color)) in stop_pairs.enumerate() {
+                let stop = &stops[i.min(stops.len() - 1)];
+                *offset = stop.0;
+                *color = stop.1.to_value();
+            }
+        }
+        instance
+    }
+}
 
 pub struct Pipeline {
     render_pipeline: wgpu::RenderPipeline,
+    /// 阴影 使用 同一个着色器的 另一组入口
+    shadow_pipeline: wgpu::RenderPipeline,
+    gradient_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     index_len: u32,
@@ -61,6 +118,8 @@ pub struct Pipeline {
     globals_bind_group: wgpu::BindGroup,
     depth_texture: Texture,
     instances_buffer: wgpu::Buffer,
+    shadows_buffer: wgpu::Buffer,
+    gradients_buffer: wgpu::Buffer,
 }
 
 impl Pipeline {
@@ -117,69 +176,75 @@ impl Pipeline {
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<Vertex>() as u64,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &wgpu::vertex_attr_array!(
-                            0 => Float32x2,
-                        ),
-                    },
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<Quad>() as u64,
-                        step_mode: wgpu::VertexStepMode::Instance,
-                        attributes: &wgpu::vertex_attr_array!(
-                            1 => Float32x2,
-                            2 => Float32x2,
-                            3 => Float32x4,
-                            4 => Float32x4,
-                            5 => Float32,
-                            6 => Float32,
-                        ),
-                    },
-                ],
+        let render_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_main", "fs_main"),
+            wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Quad>() as u64,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &wgpu::vertex_attr_array!(
+                    1 => Float32x2,
+                    2 => Float32x2,
+                    3 => Float32x4,
+                    4 => Float32x4,
+                    5 => Float32,
+                    6 => Float32,
+                ),
             },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent::REPLACE,
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                }],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                front_face: wgpu::FrontFace::Cw,
-                ..Default::default()
+        );
+
+        let shadow_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_shadow", "fs_shadow"),
+            wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Shadow>() as u64,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &wgpu::vertex_attr_array!(
+                    1 => Float32x2,
+                    2 => Float32x2,
+                    3 => Float32x4,
+                    4 => Float32x2,
+                    5 => Float32,
+                    6 => Float32,
+                    7 => Float32,
+                    8 => Float32,
+                ),
             },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: Texture::DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
+        );
+
+        let gradient_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_gradient", "fs_gradient"),
+            wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<GradientInstance>() as u64,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &wgpu::vertex_attr_array!(
+                    1 => Float32x2,
+                    2 => Float32x2,
+                    3 => Float32x4,
+                    4 => Float32x4,
+                    5 => Float32x4,
+                    6 => Float32x4,
+                    7 => Float32x4,
+                    8 => Float32x4,
+                    9 => Float32x4,
+                    10 => Float32x4,
+                    11 => Float32x4,
+                    12 => Float32x4,
+                    13 => Float32x4,
+                    14 => Float32,
+                ),
             },
-            multiview: None,
-        });
+        );
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("quad vertex buffer"),
@@ -195,7 +260,21 @@ impl Pipeline {
 
         let instances_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("quad instance buffer"),
-            size: std::mem::size_of::<Quad>() as u64 * 100_000 as u64,
+            size: (std::mem::size_of::<Quad>() * MAX_INSTANCES) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let shadows_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("shadow instance buffer"),
+            size: (std::mem::size_of::<Shadow>() * MAX_INSTANCES) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let gradients_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gradient instance buffer"),
+            size: (std::mem::size_of::<GradientInstance>() * MAX_GRADIENTS) as u64,
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -204,6 +283,8 @@ impl Pipeline {
 
         Self {
             render_pipeline,
+            shadow_pipeline,
+            gradient_pipeline,
             globals_buffer,
             globals_bind_group,
             vertex_buffer,
@@ -211,72 +292,98 @@ impl Pipeline {
             index_len,
             depth_texture,
             instances_buffer,
+            shadows_buffer,
+            gradients_buffer,
         }
     }
 
-    pub fn render(
+    /// 深度缓冲, 作为 一帧的渲染通道 的深度附件
+    pub fn depth_view(&self) -> &wgpu::TextureView {
+        &self.depth_texture.view
+    }
+
+    /// 上传 一帧中的 所有四边形, 阴影和渐变, 超出容量的部分 不绘制
+    pub fn prepare(
         &mut self,
-        device: &wgpu::Device,
-        output: wgpu::SurfaceTexture,
         queue: &wgpu::Queue,
         instances: &[Quad],
-    ) -> Result<(), wgpu::SurfaceError> {
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Render Encoder"),
-        });
-
+        shadows: &[Shadow],
+        gradients: &[GradientInstance],
+    ) {
+        let len = instances.len().min(MAX_INSTANCES);
         queue.write_buffer(
             &self.instances_buffer,
             0,
-            bytemuck::cast_slice(&instances[..]),
+            bytemuck::cast_slice(&instances[..len]),
+        );
+        let len = shadows.len().min(MAX_INSTANCES);
+        queue.write_buffer(
+            &self.shadows_buffer,
+            0,
+            bytemuck::cast_slice(&shadows[..len]),
         );
+        let len = gradients.len().min(MAX_GRADIENTS);
+        queue.write_buffer(
+            &self.gradients_buffer,
+            0,
+            bytemuck::cast_slice(&gradients[..len]),
+        );
+    }
 
-        let inctence_len = instances.len();
-
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_texture.view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: true,
-                    }),
-                    stencil_ops: None,
-                }),
-            });
+    /// 绘制 prepare 上传的 四边形中的 一个范围
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, instances: Range<u32>) {
+        self.draw_instances(
+            render_pass,
+            &self.render_pipeline,
+            &self.instances_buffer,
+            instances,
+        );
+    }
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
+    /// 绘制 prepare 上传的 阴影中的 一个范围
+    pub fn draw_shadows<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, shadows: Range<u32>) {
+        self.draw_instances(
+            render_pass,
+            &self.shadow_pipeline,
+            &self.shadows_buffer,
+            shadows,
+        );
+    }
 
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, self.instances_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+    /// 绘制 prepare 上传的 渐变中的 一个范围
+    pub fn draw_gradients<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        gradients: Range<u32>,
+    ) {
+        let max = MAX_GRADIENTS as u32;
+        self.draw_instances(
+            render_pass,
+            &self.gradient_pipeline,
+            &self.gradients_buffer,
+            gradients.start.min(max)..gradients.end.min(max),
+        );
+    }
 
-            render_pass.draw_indexed(0..self.index_len, 0, 0..inctence_len as u32);
+    fn draw_instances<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a wgpu::RenderPipeline,
+        buffer: &'a wgpu::Buffer,
+        instances: Range<u32>,
+    ) {
+        let max = MAX_INSTANCES as u32;
+        let instances = instances.start.min(max)..instances.end.min(max);
+        if instances.is_empty() {
+            return;
         }
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
 
-        queue.submit(iter::once(encoder.finish()));
-        output.present();
-
-        Ok(())
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.index_len, 0, instances);
     }
 
     pub fn resize(
@@ -295,6 +402,70 @@ impl Pipeline {
     }
 }
 
+/// 四边形, 阴影和渐变 共用的 管线配置: 第 0 个顶点缓冲 是单位正方形, 第 1 个 是实例
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    (vs_entry, fs_entry): (&str, &str),
+    instance_layout: wgpu::VertexBufferLayout,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: vs_entry,
+            buffers: &[
+                wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Vertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array!(
+                        0 => Float32x2,
+                    ),
+                },
+                instance_layout,
+            ],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: fs_entry,
+            targets: &[wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::SrcAlpha,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent::REPLACE,
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            }],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            front_face: wgpu::FrontFace::Cw,
+            ..Default::default()
+        },
+        // 同一帧中 按绘制顺序 覆盖
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
 pub struct Texture {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
@@ -124,3 +124,272 @@ fn fs_main(
 
     return vec4<f32>(mixed_color.x, mixed_color.y, mixed_color.z, mixed_color.w * radius_alpha);
 }
+
+// 阴影: 高斯模糊的 圆角矩形, 计算方法 和 xinghuo_geom::Shadow::alpha 相同
+
+struct ShadowInput {
+    [[location(0)]] v_pos: vec2<f32>;
+    [[location(1)]] pos: vec2<f32>;
+    [[location(2)]] size: vec2<f32>;
+    [[location(3)]] color: vec4<f32>;
+    [[location(4)]] offset: vec2<f32>;
+    [[location(5)]] blur: f32;
+    [[location(6)]] spread: f32;
+    [[location(7)]] border_radius: f32;
+    [[location(8)]] inset: f32;
+};
+
+struct ShadowOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] pos: vec2<f32>;
+    [[location(1)]] size: vec2<f32>;
+    [[location(2)]] color: vec4<f32>;
+    [[location(3)]] shape_pos: vec2<f32>;
+    [[location(4)]] shape_size: vec2<f32>;
+    [[location(5)]] shape_radius: f32;
+    [[location(6)]] border_radius: f32;
+    [[location(7)]] sigma: f32;
+    [[location(8)]] inset: f32;
+};
+
+[[stage(vertex)]]
+fn vs_shadow(input: ShadowInput) -> ShadowOutput {
+    var out: ShadowOutput;
+
+    // 没有模糊时 也保留 半个像素的 抗锯齿
+    let sigma = max(input.blur / 2.0, 0.5);
+    var spread: f32 = input.spread;
+    if (input.inset > 0.0) {
+        spread = -spread;
+    }
+    let shape_pos = input.pos + input.offset - vec2<f32>(spread, spread);
+    let shape_size = max(input.size + vec2<f32>(spread, spread) * 2.0, vec2<f32>(0.0, 0.0));
+
+    // 外阴影 覆盖 3 倍标准差 的模糊范围, 内阴影 只在矩形内
+    var pos: vec2<f32> = shape_pos - vec2<f32>(3.0 * sigma, 3.0 * sigma);
+    var size: vec2<f32> = shape_size + vec2<f32>(6.0 * sigma, 6.0 * sigma);
+    if (input.inset > 0.0) {
+        pos = input.pos;
+        size = input.size;
+    }
+
+    var transform: mat4x4<f32> = mat4x4<f32>(
+        vec4<f32>(size.x, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, size.y, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 1.0, 0.0),
+        vec4<f32>(pos, 0.0, 1.0)
+    );
+
+    out.pos = input.pos;
+    out.size = input.size;
+    out.color = input.color;
+    out.shape_pos = shape_pos;
+    out.shape_size = shape_size;
+    out.shape_radius = min(
+        max(input.border_radius + spread, 0.0),
+        min(shape_size.x, shape_size.y) / 2.0
+    );
+    out.border_radius = min(input.border_radius, min(input.size.x, input.size.y) / 2.0);
+    out.sigma = sigma;
+    out.inset = input.inset;
+    out.position = globals.transform * transform * vec4<f32>(input.v_pos, 0.0, 1.0);
+
+    return out;
+}
+
+// 误差函数的 近似
+fn erf(x: vec2<f32>) -> vec2<f32> {
+    let s = sign(x);
+    let a = abs(x);
+    var r: vec2<f32> = vec2<f32>(1.0, 1.0) + (vec2<f32>(0.278393, 0.278393) + (vec2<f32>(0.230389, 0.230389) + 0.078108 * a * a) * a) * a;
+    r = r * r;
+    return s - s / (r * r);
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    let pi = 3.141592653589793;
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (sqrt(2.0 * pi) * sigma);
+}
+
+// 高斯模糊后的 圆角矩形 在 y 方向的一行上 沿 x 的积分
+fn rounded_box_shadow_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: vec2<f32>) -> f32 {
+    let delta = min(half_size.y - corner - abs(y), 0.0);
+    let curved = half_size.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    let integral = vec2<f32>(0.5, 0.5) + 0.5 * erf((vec2<f32>(x, x) + vec2<f32>(-curved, curved)) * (sqrt(0.5) / sigma));
+    return integral.y - integral.x;
+}
+
+// 高斯模糊后的 圆角矩形 在 point 处的覆盖率: x 方向 解析积分, y 方向 取 4 个样本
+fn rounded_box_shadow(pos: vec2<f32>, size: vec2<f32>, corner: f32, point: vec2<f32>, sigma: f32) -> f32 {
+    let half_size = size / 2.0;
+    let p = point - (pos + half_size);
+    let low = p.y - half_size.y;
+    let high = p.y + half_size.y;
+    let start = clamp(-3.0 * sigma, low, high);
+    let end = clamp(3.0 * sigma, low, high);
+    let step = (end - start) / 4.0;
+    var y: f32 = start + step * 0.5;
+    var value: f32 = 0.0;
+    for (var i: i32 = 0; i < 4; i = i + 1) {
+        value = value + rounded_box_shadow_x(p.x, p.y - y, sigma, corner, half_size) * gaussian(y, sigma) * step;
+        y = y + step;
+    }
+    return value;
+}
+
+[[stage(fragment)]]
+fn fs_shadow(
+    input: ShadowOutput
+) -> [[location(0)]] vec4<f32> {
+    let point = vec2<f32>(input.position.x, input.position.y);
+    let coverage = rounded_box_shadow(
+        input.shape_pos,
+        input.shape_size,
+        input.shape_radius,
+        point,
+        input.sigma
+    );
+
+    // 外阴影 不绘制在 矩形内, 内阴影 只绘制在 矩形内
+    let dist = distance_between_vertex_quad(point, input.pos, input.size, input.border_radius);
+    let inside = 1.0 - smoothStep(
+        input.border_radius - 0.5,
+        input.border_radius + 0.5,
+        dist
+    );
+    var alpha: f32 = coverage * (1.0 - inside);
+    if (input.inset > 0.0) {
+        alpha = (1.0 - coverage) * inside;
+    }
+
+    return vec4<f32>(input.color.x, input.color.y, input.color.z, input.color.w * alpha);
+}
+
+// 渐变: 位置的计算 和 xinghuo_geom::Gradient::progress 相同
+
+struct GradientInput {
+    [[location(0)]] v_pos: vec2<f32>;
+    [[location(1)]] pos: vec2<f32>;
+    [[location(2)]] size: vec2<f32>;
+    [[location(3)]] params: vec4<f32>;
+    [[location(4)]] color0: vec4<f32>;
+    [[location(5)]] color1: vec4<f32>;
+    [[location(6)]] color2: vec4<f32>;
+    [[location(7)]] color3: vec4<f32>;
+    [[location(8)]] color4: vec4<f32>;
+    [[location(9)]] color5: vec4<f32>;
+    [[location(10)]] color6: vec4<f32>;
+    [[location(11)]] color7: vec4<f32>;
+    [[location(12)]] offsets0: vec4<f32>;
+    [[location(13)]] offsets1: vec4<f32>;
+    [[location(14)]] border_radius: f32;
+};
+
+struct GradientOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] pos: vec2<f32>;
+    [[location(1)]] size: vec2<f32>;
+    [[location(2)]] params: vec4<f32>;
+    [[location(3)]] color0: vec4<f32>;
+    [[location(4)]] color1: vec4<f32>;
+    [[location(5)]] color2: vec4<f32>;
+    [[location(6)]] color3: vec4<f32>;
+    [[location(7)]] color4: vec4<f32>;
+    [[location(8)]] color5: vec4<f32>;
+    [[location(9)]] color6: vec4<f32>;
+    [[location(10)]] color7: vec4<f32>;
+    [[location(11)]] offsets0: vec4<f32>;
+    [[location(12)]] offsets1: vec4<f32>;
+    [[location(13)]] border_radius: f32;
+};
+
+[[stage(vertex)]]
+fn vs_gradient(input: GradientInput) -> GradientOutput {
+    var out: GradientOutput;
+
+    var transform: mat4x4<f32> = mat4x4<f32>(
+        vec4<f32>(input.size.x, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, input.size.y, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 1.0, 0.0),
+        vec4<f32>(input.pos, 0.0, 1.0)
+    );
+
+    out.pos = input.pos;
+    out.size = input.size;
+    out.params = input.params;
+    out.color0 = input.color0;
+    out.color1 = input.color1;
+    out.color2 = input.color2;
+    out.color3 = input.color3;
+    out.color4 = input.color4;
+    out.color5 = input.color5;
+    out.color6 = input.color6;
+    out.color7 = input.color7;
+    out.offsets0 = input.offsets0;
+    out.offsets1 = input.offsets1;
+    out.border_radius = min(input.border_radius, min(input.size.x, input.size.y) / 2.0);
+    out.position = globals.transform * transform * vec4<f32>(input.v_pos, 0.0, 1.0);
+
+    return out;
+}
+
+// params: 种类 (0 线性, 1 径向, 2 锥形), 角度 (弧度, 0 指向上方 顺时针增加), 相对的中心
+fn gradient_progress(point: vec2<f32>, pos: vec2<f32>, size: vec2<f32>, params: vec4<f32>) -> f32 {
+    if (params.x < 0.5) {
+        let dir = vec2<f32>(sin(params.y), -cos(params.y));
+        let line = abs(size.x * dir.x) + abs(size.y * dir.y);
+        if (line <= 0.0) {
+            return 0.0;
+        }
+        return dot(point - (pos + size / 2.0), dir) / line + 0.5;
+    }
+    let center = pos + vec2<f32>(params.z, params.w) * size;
+    let d = point - center;
+    if (params.x < 1.5) {
+        // 椭圆 延伸到 最远的角
+        let corner = max(center - pos, pos + size - center);
+        let radius = max(corner * sqrt(2.0), vec2<f32>(0.00001, 0.00001));
+        return length(d / radius);
+    }
+    let turn = (atan2(d.x, -d.y) - params.y) / 6.283185307179586;
+    return turn - floor(turn);
+}
+
+[[stage(fragment)]]
+fn fs_gradient(
+    input: GradientOutput
+) -> [[location(0)]] vec4<f32> {
+    let point = vec2<f32>(input.position.x, input.position.y);
+    let t = gradient_progress(point, input.pos, input.size, input.params);
+
+    var colors: array<vec4<f32>, 8> = array<vec4<f32>, 8>(
+        input.color0, input.color1, input.color2, input.color3,
+        input.color4, input.color5, input.color6, input.color7
+    );
+    var offsets: array<f32, 8> = array<f32, 8>(
+        input.offsets0.x, input.offsets0.y, input.offsets0.z, input.offsets0.w,
+        input.offsets1.x, input.offsets1.y, input.offsets1.z, input.offsets1.w
+    );
+
+    // 色标之间 线性插值, 两端之外 取端点的颜色
+    var color: vec4<f32> = colors[7];
+    if (t <= offsets[0]) {
+        color = colors[0];
+    } else {
+        for (var i: i32 = 1; i < 8; i = i + 1) {
+            if (t <= offsets[i]) {
+                let k = (t - offsets[i - 1]) / max(offsets[i] - offsets[i - 1], 0.00001);
+                color = mix(colors[i - 1], colors[i], vec4<f32>(k, k, k, k));
+                break;
+            }
+        }
+    }
+
+    let dist = distance_between_vertex_quad(point, input.pos, input.size, input.border_radius);
+    let radius_alpha = 1.0 - smoothStep(
+        max(input.border_radius - 0.5, 0.0),
+        input.border_radius + 0.5,
+        dist
+    );
+    return vec4<f32>(color.x, color.y, color.z, color.w * radius_alpha);
+}
@@ -0,0 +1,42 @@
+struct Globals {
+    transform: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]] var<uniform> globals: Globals;
+[[group(1), binding(0)]] var atlas: texture_2d<f32>;
+[[group(1), binding(1)]] var atlas_sampler: sampler;
+
+struct VertexInput {
+    [[location(0)]] v_pos: vec2<f32>;
+    [[location(1)]] pos: vec2<f32>;
+    [[location(2)]] size: vec2<f32>;
+    [[location(3)]] uv_min: vec2<f32>;
+    [[location(4)]] uv_max: vec2<f32>;
+    [[location(5)]] color: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // 字形位图的 屏幕坐标, 和 在图集中的 纹理坐标
+    let pos = input.pos + input.v_pos * input.size;
+    out.uv = mix(input.uv_min, input.uv_max, input.v_pos);
+    out.color = input.color;
+    out.position = globals.transform * vec4<f32>(pos, 0.0, 1.0);
+
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    // 图集中 保存的是 覆盖率
+    let coverage = textureSample(atlas, atlas_sampler, input.uv).r;
+    return vec4<f32>(input.color.rgb, input.color.a * coverage);
+}
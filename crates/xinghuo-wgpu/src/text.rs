@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::ops::Range;
+
+use wgpu::util::DeviceExt;
+use xinghuo_core::prelude::{glam::Vec2, Color};
+use xinghuo_core::text::{FontId, FontSystem, Glyph};
+
+use crate::quad::{Globals, Texture, QUAD_INDICES};
+
+/// 字形图集的 边长
+const ATLAS_SIZE: u32 = 1024;
+
+/// 一帧中 最多绘制的 字形数量
+const MAX_INSTANCES: usize = 100_000;
+
+/// 常见系统字体的路径, 优先 覆盖中日韩文字的字体
+const FALLBACK_FONTS: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Helvetica.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\segoeui.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// 没有加载字体时 加载后备字体: 环境变量 XINGHUO_FONT 指定的字体文件, 或者 常见的系统字体;
+/// 返回 默认字体, 找不到字体时 返回 None, 文本只参与布局, 不绘制
+pub fn load_fallback_font(fonts: &FontSystem) -> Option<FontId> {
+    if fonts.font(FontId(0)).is_some() {
+        return Some(FontId(0));
+    }
+    let env = std::env::var("XINGHUO_FONT").ok();
+    env.as_deref()
+        .into_iter()
+        .chain(FALLBACK_FONTS.iter().copied())
+        .filter_map(|path| std::fs::read(path).ok())
+        .find_map(|data| fonts.load(data).ok())
+}
+
+/// 绘制一个字形 的实例数据
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GlyphInstance {
+    pub pos: [f32; 2],
+    pub size: [f32; 2],
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// 字形在图集中的 位置
+#[derive(Debug, Clone, Copy)]
+struct AtlasGlyph {
+    /// 位图左上角 相对于 字形基线起点 的偏移
+    offset: Vec2,
+    size: Vec2,
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+}
+
+/// 按行 (shelf) 分配 图集空间
+#[derive(Debug, Default)]
+struct ShelfPacker {
+    x: u32,
+    y: u32,
+    row_height: u32,
+}
+
+impl ShelfPacker {
+    /// 字形之间 留 1 像素间隔, 避免采样时 混入相邻字形
+    fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        if width + 1 > ATLAS_SIZE || height + 1 > ATLAS_SIZE {
+            return None;
+        }
+        if self.x + width + 1 > ATLAS_SIZE {
+            self.x = 0;
+            self.y += self.row_height;
+            self.row_height = 0;
+        }
+        if self.y + height + 1 > ATLAS_SIZE {
+            return None;
+        }
+        let pos = [self.x, self.y];
+        self.x += width + 1;
+        self.row_height = self.row_height.max(height + 1);
+        Some(pos)
+    }
+}
+
+/// 字形缓存的键: 字体, 字形, 字号
+type GlyphKey = (FontId, u16, u32);
+
+pub struct Pipeline {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    globals_buffer: wgpu::Buffer,
+    globals_bind_group: wgpu::BindGroup,
+    atlas: Texture,
+    atlas_bind_group: wgpu::BindGroup,
+    packer: ShelfPacker,
+    /// 已光栅化的字形, None 表示 没有轮廓
+    cache: HashMap<GlyphKey, Option<AtlasGlyph>>,
+    instances_buffer: wgpu::Buffer,
+}
+
+impl Pipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> Self {
+        let globals_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Text Globals Buffer"),
+            contents: bytemuck::cast_slice(&[Globals::new(size.width as f32, size.height as f32)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/text.wgsl").into()),
+        });
+
+        let globals_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Text globals bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<Globals>() as u64
+                        ),
+                    },
+                    count: None,
+                }],
+            });
+
+        let globals_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Text globals bind group"),
+            layout: &globals_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(globals_buffer.as_entire_buffer_binding()),
+            }],
+        });
+
+        // 字形图集: 单通道 覆盖率
+        let atlas = Texture::new(
+            device,
+            ATLAS_SIZE,
+            ATLAS_SIZE,
+            wgpu::TextureFormat::R8Unorm,
+            Some("glyph atlas"),
+        );
+
+        let atlas_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Text atlas bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let atlas_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Text atlas bind group"),
+            layout: &atlas_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&atlas.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&atlas.sampler),
+                },
+            ],
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Text Render Pipeline Layout"),
+                bind_group_layouts: &[&globals_bind_group_layout, &atlas_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 2]>() as u64,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array!(
+                            0 => Float32x2,
+                        ),
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<GlyphInstance>() as u64,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &wgpu::vertex_attr_array!(
+                            1 => Float32x2,
+                            2 => Float32x2,
+                            3 => Float32x2,
+                            4 => Float32x2,
+                            5 => Float32x4,
+                        ),
+                    },
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::SrcAlpha,
+                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::REPLACE,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Cw,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("text vertex buffer"),
+            contents: bytemuck::cast_slice(&[[0.0f32, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("text index buffer"),
+            contents: bytemuck::cast_slice(&QUAD_INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let instances_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("text instance buffer"),
+            size: (std::mem::size_of::<GlyphInstance>() * MAX_INSTANCES) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            globals_buffer,
+            globals_bind_group,
+            atlas,
+            atlas_bind_group,
+            packer: ShelfPacker::default(),
+            cache: HashMap::new(),
+            instances_buffer,
+        }
+    }
+
+    /// 把字形 转换为 绘制实例, 新字形 光栅化后 写入图集
+    pub fn push(
+        &mut self,
+        queue: &wgpu::Queue,
+        fonts: &FontSystem,
+        glyphs: &[Glyph],
+        color: Color,
+        instances: &mut Vec<GlyphInstance>,
+    ) {
+        let color = color.to_value();
+        for glyph in glyphs {
+            if let Some(atlas_glyph) = self.atlas_glyph(queue, fonts, glyph) {
+                let pos = (glyph.position + atlas_glyph.offset).round();
+                instances.push(GlyphInstance {
+                    pos: pos.into(),
+                    size: atlas_glyph.size.into(),
+                    uv_min: atlas_glyph.uv_min,
+                    uv_max: atlas_glyph.uv_max,
+                    color,
+                });
+            }
+        }
+    }
+
+    fn atlas_glyph(
+        &mut self,
+        queue: &wgpu::Queue,
+        fonts: &FontSystem,
+        glyph: &Glyph,
+    ) -> Option<AtlasGlyph> {
+        let key = (glyph.font, glyph.id, glyph.size.to_bits());
+        if let Some(cached) = self.cache.get(&key) {
+            return *cached;
+        }
+
+        let raster = fonts.font(glyph.font)?.rasterize(glyph.id, glyph.size);
+        let atlas_glyph = match raster {
+            Some(raster) => {
+                let pos = match self.packer.allocate(raster.width, raster.height) {
+                    Some(pos) => pos,
+                    None => {
+                        // 图集已满: 清空后 重新分配, 之前的字形 在下次使用时 重新光栅化
+                        // 同一帧中 已经生成的实例 可能引用到 被覆盖的区域
+                        self.cache.clear();
+                        self.packer = ShelfPacker::default();
+                        self.packer.allocate(raster.width, raster.height)?
+                    }
+                };
+                queue.write_texture(
+                    wgpu::ImageCopyTexture {
+                        texture: &self.atlas.texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d {
+                            x: pos[0],
+                            y: pos[1],
+                            z: 0,
+                        },
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    &raster.coverage,
+                    wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(raster.width),
+                        rows_per_image: NonZeroU32::new(raster.height),
+                    },
+                    wgpu::Extent3d {
+                        width: raster.width,
+                        height: raster.height,
+                        depth_or_array_layers: 1,
+                    },
+                );
+                let atlas_size = ATLAS_SIZE as f32;
+                Some(AtlasGlyph {
+                    offset: raster.offset,
+                    size: Vec2::new(raster.width as f32, raster.height as f32),
+                    uv_min: [pos[0] as f32 / atlas_size, pos[1] as f32 / atlas_size],
+                    uv_max: [
+                        (pos[0] + raster.width) as f32 / atlas_size,
+                        (pos[1] + raster.height) as f32 / atlas_size,
+                    ],
+                })
+            }
+            None => None,
+        };
+        self.cache.insert(key, atlas_glyph);
+        atlas_glyph
+    }
+
+    /// 上传 一帧中的 所有字形实例, 超出容量的部分 不绘制
+    pub fn prepare(&mut self, queue: &wgpu::Queue, instances: &[GlyphInstance]) {
+        let len = instances.len().min(MAX_INSTANCES);
+        queue.write_buffer(
+            &self.instances_buffer,
+            0,
+            bytemuck::cast_slice(&instances[..len]),
+        );
+    }
+
+    /// 绘制 prepare 上传的 字形实例中的 一个范围
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, instances: Range<u32>) {
+        let max = MAX_INSTANCES as u32;
+        let instances = instances.start.min(max)..instances.end.min(max);
+        if instances.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.atlas_bind_group, &[]);
+
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instances_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, instances);
+    }
+
+    pub fn resize(&mut self, queue: &wgpu::Queue, new_size: &[f32; 2]) {
+        queue.write_buffer(
+            &self.globals_buffer,
+            0,
+            bytemuck::cast_slice(&[Globals::new(new_size[0], new_size[1])]),
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use winit::{
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopBuilder},
     window,
 };
@@ -57,7 +57,12 @@ impl Window {
     }
 }
 
+/// 滚轮滚动一行 对应的像素
+const LINE_HEIGHT: f32 = 20.0;
+
 fn run_native(window: window::Window, event_loop: EventLoop<CustomEvent>, mut app: App) {
+    // 鼠标位置
+    let mut cursor = Vec2::ZERO;
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Wait;
         match event {
@@ -69,8 +74,68 @@ fn run_native(window: window::Window, event_loop: EventLoop<CustomEvent>, mut ap
                 app.resize([size.width as f32, size.height as f32]);
             }
 
+            Event::WindowEvent {
+                event: WindowEvent::ScaleFactorChanged { scale_factor, .. },
+                ..
+            } => {
+                app.rescale(scale_factor as f32);
+                window.request_redraw();
+            }
+
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                ..
+            } => {
+                cursor = vec2(position.x as f32, position.y as f32);
+                if app.pointer_move(cursor) {
+                    window.request_redraw();
+                }
+            }
+
+            Event::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                ..
+            } => {
+                // 向上滚动时 delta 为正, 滚动偏移量 减小
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => -vec2(x, y) * LINE_HEIGHT,
+                    MouseScrollDelta::PixelDelta(p) => -vec2(p.x as f32, p.y as f32),
+                };
+                if app.scroll(cursor, delta) {
+                    window.request_redraw();
+                }
+            }
+
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state,
+                        button: MouseButton::Left,
+                        ..
+                    },
+                ..
+            } => {
+                let changed = match state {
+                    ElementState::Pressed => app.pointer_down(cursor),
+                    ElementState::Released => app.pointer_up(cursor),
+                };
+                if changed {
+                    window.request_redraw();
+                }
+            }
+
             Event::RedrawRequested(window_id) => {
                 app.render();
+                if app.is_animating() {
+                    window.request_redraw();
+                }
+            }
+
+            // 事件回调中 操作了 暂停的动画
+            Event::MainEventsCleared => {
+                if app.take_frame_request() {
+                    window.request_redraw();
+                }
             }
 
             Event::WindowEvent {
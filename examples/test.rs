@@ -291,7 +291,7 @@ fn header() -> Element {
 }
 
 fn center() -> Element {
-    div().width("100%").height("100%").onclick(|clicked| {
+    div().width("100%").height("100% - 60").onclick(|clicked| {
         println!("{:?}", &clicked);
     })
 }